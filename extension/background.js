@@ -3,6 +3,7 @@
 const CHROME_URLS = {
     SET_PROXY: 'chrome://set_proxy/',
     RESET_PROXY: 'chrome://reset_proxy',
+    PROXY_BYPASS: 'chrome://proxy_bypass',
     CLEAR_DATA: 'chrome://clear_data',
     CLEAR_COOKIES: 'chrome://clear_cookies',
     INIT_EXTENSION: 'chrome://init_extension',
@@ -13,6 +14,8 @@ const CHROME_URLS = {
 class ProxyManager {
     constructor() {
         this.currentAuthHandler = null;
+        this.currentConfig = null;
+        this.bypassList = ["localhost"];
     }
 
     /**
@@ -82,6 +85,7 @@ class ProxyManager {
 
             // Remove existing auth listeners
             this.removeAuthListener();
+            this.currentConfig = proxyConfig;
 
             // Create new auth handler
             const authHandler = this.createAuthHandler(proxyConfig);
@@ -95,7 +99,7 @@ class ProxyManager {
                         host: proxyConfig.host,
                         port: proxyConfig.port,
                     },
-                    bypassList: ["localhost"]
+                    bypassList: this.bypassList
                 }
             };
 
@@ -175,11 +179,27 @@ class ProxyManager {
         }
     }
 
+    /**
+     * Update hosts that bypass the proxy and re-apply active settings
+     * @param {URL} parsedUrl - Parsed URL object
+     */
+    setBypass(parsedUrl) {
+        const hosts = (parsedUrl.searchParams.get('hosts') || '')
+            .split(',')
+            .map(host => host.trim())
+            .filter(host => host.length > 0);
+        this.bypassList = ["localhost", ...hosts];
+        if (this.currentConfig) {
+            this.setProxy(this.currentConfig);
+        }
+    }
+
     /**
      * Reset proxy to system settings
      */
     resetProxy() {
         this.removeAuthListener();
+        this.currentConfig = null;
         chrome.proxy.settings.set(
             { value: { mode: 'system' }, scope: 'regular' },
             () => {
@@ -262,6 +282,8 @@ class CommandHandler {
                 return proxyConfig && (this.proxyManager.setProxy(proxyConfig), true);
             case url.startsWith(CHROME_URLS.RESET_PROXY):
                 return (this.proxyManager.resetProxy(), true);
+            case url.startsWith(CHROME_URLS.PROXY_BYPASS):
+                return (this.proxyManager.setBypass(parsedUrl), true);
             case url.startsWith(CHROME_URLS.CLEAR_DATA):
                 return (BrowserDataManager.removeBrowsingData(), true);
             case url.startsWith(CHROME_URLS.CLEAR_COOKIES):
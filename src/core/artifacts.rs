@@ -0,0 +1,312 @@
+use std::{
+    fs,
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tokio::task::JoinHandle;
+use tokio_stream::StreamExt;
+use chromiumoxide::{
+    cdp::js_protocol::runtime::EventConsoleApiCalled,
+    page::ScreenshotParams,
+    Page
+};
+
+use crate::error::BrowserError;
+use super::browser::BrowserSession;
+use super::config::EffectiveConfig;
+use super::identity::unix_now;
+use super::network::{NetworkEntry, NetworkRecorder};
+
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConsoleEntry {
+    pub level: String,
+    pub text: String,
+    pub timestamp: f64,
+}
+
+pub struct ConsoleRecorder {
+    entries: Arc<Mutex<Vec<ConsoleEntry>>>,
+    handle: JoinHandle<()>,
+}
+
+impl ConsoleRecorder {
+    pub async fn start(page: &Page) -> Result<Self, BrowserError> {
+        let mut events = page.event_listener::<EventConsoleApiCalled>().await?;
+        let entries = Arc::new(Mutex::new(Vec::new()));
+        let task_entries = entries.clone();
+        let handle = tokio::task::spawn(async move {
+            while let Some(event) = events.next().await {
+                let text = event.args.iter()
+                    .map(|arg| match (&arg.value, &arg.description) {
+                        (Some(Value::String(s)), _) => s.clone(),
+                        (Some(value), _) => value.to_string(),
+                        (None, Some(description)) => description.clone(),
+                        (None, None) => String::new()
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                task_entries.lock().unwrap().push(ConsoleEntry {
+                    level: event.r#type.as_ref().to_owned(),
+                    text,
+                    timestamp: *event.timestamp.inner()
+                });
+            }
+        });
+
+        Ok (
+            Self {
+                entries,
+                handle
+            }
+        )
+    }
+
+    pub fn entries(&self) -> Vec<ConsoleEntry> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    pub fn stop(self) -> Vec<ConsoleEntry> {
+        self.handle.abort();
+        self.entries()
+    }
+}
+
+impl Drop for ConsoleRecorder {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+fn iso8601(epoch_secs: f64) -> String {
+    let millis = (epoch_secs * 1000.0).round() as i64;
+    let (days, ms_of_day) = (millis.div_euclid(86_400_000), millis.rem_euclid(86_400_000));
+    let era = (days + 719_468).div_euclid(146_097);
+    let doe = (days + 719_468).rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        ms_of_day / 3_600_000,
+        ms_of_day / 60_000 % 60,
+        ms_of_day / 1000 % 60,
+        ms_of_day % 1000
+    )
+}
+
+pub fn entries_to_har(entries: &[NetworkEntry]) -> Value {
+    let har_entries: Vec<Value> = entries.iter()
+        .map(|e| {
+            let time = e.finished
+                .map(|f| (f - e.started) * 1000.0)
+                .unwrap_or(-1.0);
+            json!({
+                "startedDateTime": iso8601(e.wall_time.unwrap_or_default()),
+                "time": time,
+                "request": {
+                    "method": e.method,
+                    "url": e.url,
+                    "httpVersion": "",
+                    "headers": [],
+                    "queryString": [],
+                    "cookies": [],
+                    "headersSize": -1,
+                    "bodySize": -1
+                },
+                "response": {
+                    "status": e.status.unwrap_or(0),
+                    "statusText": e.error.clone().unwrap_or_default(),
+                    "httpVersion": "",
+                    "headers": [],
+                    "cookies": [],
+                    "content": {
+                        "size": e.encoded_size,
+                        "mimeType": e.mime_type.clone().unwrap_or_default()
+                    },
+                    "redirectURL": "",
+                    "headersSize": -1,
+                    "bodySize": e.encoded_size
+                },
+                "cache": {},
+                "timings": {
+                    "send": 0,
+                    "wait": time,
+                    "receive": 0
+                },
+                "_resourceType": e.resource_type
+            })
+        })
+        .collect();
+    json!({
+        "log": {
+            "version": "1.2",
+            "creator": {
+                "name": env!("CARGO_PKG_NAME"),
+                "version": env!("CARGO_PKG_VERSION")
+            },
+            "entries": har_entries
+        }
+    })
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ArtifactIndex {
+    pub job_id: String,
+    pub url: Option<String>,
+    pub created_at: u64,
+    pub files: Vec<String>,
+    pub metadata: Map<String, Value>,
+    #[serde(default)]
+    pub ssl_key_log: Option<String>,
+    #[serde(default)]
+    pub pcap: Option<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct JobArtifacts {
+    pub job_id: String,
+    pub url: Option<String>,
+    pub html: Option<String>,
+    pub screenshot: Option<Vec<u8>>,
+    pub network: Vec<NetworkEntry>,
+    pub console: Vec<ConsoleEntry>,
+    pub data: Option<Value>,
+    pub metadata: Map<String, Value>,
+    pub config: Option<EffectiveConfig>,
+    pub pcap: Option<String>,
+}
+
+impl JobArtifacts {
+    pub fn new(job_id: &str) -> Self {
+        Self {
+            job_id: job_id.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    pub fn with_metadata(mut self, key: &str, value: Value) -> Self {
+        self.metadata.insert(key.into(), value);
+        self
+    }
+
+    pub fn with_pcap(mut self, path: &str) -> Self {
+        self.pcap = Some(path.into());
+        self
+    }
+
+    pub async fn capture(&mut self, page: &Page) -> Result<(), BrowserError> {
+        self.url = page.url().await?;
+        self.html = Some(page.content().await?);
+        self.screenshot = Some(
+            page.screenshot(ScreenshotParams::builder().full_page(true).build()).await?
+        );
+        Ok(())
+    }
+
+    pub fn files(&self) -> io::Result<Vec<(String, Vec<u8>)>> {
+        let mut files: Vec<(String, Vec<u8>)> = Vec::new();
+        if let Some(html) = &self.html {
+            files.push(("page.html".into(), html.clone().into_bytes()));
+        }
+        if let Some(screenshot) = &self.screenshot {
+            files.push(("screenshot.png".into(), screenshot.clone()));
+        }
+        if !self.network.is_empty() {
+            files.push(("network.har".into(), to_json(&entries_to_har(&self.network))?));
+        }
+        if !self.console.is_empty() {
+            files.push(("console.json".into(), to_json(&self.console)?));
+        }
+        if let Some(data) = &self.data {
+            files.push(("data.json".into(), to_json(data)?));
+        }
+        if let Some(config) = &self.config {
+            files.push(("effective_config.json".into(), to_json(config)?));
+        }
+        let index = ArtifactIndex {
+            job_id: self.job_id.clone(),
+            url: self.url.clone(),
+            created_at: unix_now(),
+            files: files.iter().map(|(name, _)| name.clone()).collect(),
+            metadata: self.metadata.clone(),
+            ssl_key_log: self.config.as_ref()
+                .and_then(|c| c.ssl_key_log.clone()),
+            pcap: self.pcap.clone()
+        };
+        files.push(("index.json".into(), to_json(&index)?));
+        Ok(files)
+    }
+
+    pub fn write_dir(&self, dir: impl AsRef<Path>) -> io::Result<PathBuf> {
+        let root = dir.as_ref().join(&self.job_id);
+        fs::create_dir_all(&root)?;
+        for (name, data) in self.files()? {
+            fs::write(root.join(name), data)?;
+        }
+        Ok(root)
+    }
+
+    #[cfg(feature = "zip")]
+    pub fn write_zip(&self, dir: impl AsRef<Path>) -> io::Result<PathBuf> {
+        use std::io::Write;
+        use zip::{write::SimpleFileOptions, ZipWriter};
+
+        fs::create_dir_all(dir.as_ref())?;
+        let path = dir.as_ref().join(format!("{}.zip", self.job_id));
+        let mut zip = ZipWriter::new(fs::File::create(&path)?);
+        for (name, data) in self.files()? {
+            zip.start_file(name, SimpleFileOptions::default())
+                .map_err(io::Error::other)?;
+            zip.write_all(&data)?;
+        }
+        zip.finish().map_err(io::Error::other)?;
+        Ok(path)
+    }
+}
+
+pub(crate) fn to_json<T: Serialize>(value: &T) -> io::Result<Vec<u8>> {
+    serde_json::to_vec_pretty(value).map_err(io::Error::other)
+}
+
+impl BrowserSession {
+    pub async fn open_with_artifacts(&self, url: &str, job_id: &str) -> Result<(Page, JobArtifacts), BrowserError> {
+        let page = self.new_page().await?;
+        let recorder = NetworkRecorder::start(&page).await?;
+        let console = ConsoleRecorder::start(&page).await?;
+        self.open_on_page(url, &page).await?;
+        let mut artifacts = JobArtifacts::new(job_id);
+        artifacts.capture(&page).await?;
+        artifacts.network = recorder.drain(
+            Duration::from_millis(500), Duration::from_secs(10)
+        ).await;
+        artifacts.console = console.stop();
+        artifacts.config = Some(self.effective_config.clone());
+
+        Ok((page, artifacts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn har_dates_are_rfc3339() {
+        assert_eq!(iso8601(0.0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(iso8601(951_782_400.5), "2000-02-29T00:00:00.500Z");
+        assert_eq!(iso8601(1_700_000_000.123), "2023-11-14T22:13:20.123Z");
+    }
+}
@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use chromiumoxide::Page;
+
+use crate::error::BrowserError;
+
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkStatus {
+    pub url: String,
+    pub status: Option<u16>,
+    pub redirected_to: Option<String>,
+    pub load_time: f64,
+    pub opaque: bool,
+    pub error: Option<String>,
+}
+
+impl LinkStatus {
+    pub fn is_broken(&self) -> bool {
+        self.error.is_some() || self.status.map(|s| s >= 400).unwrap_or(false)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LinkCheckOptions {
+    pub concurrency: usize,
+    pub timeout: u64,
+    pub same_origin_only: bool,
+}
+
+impl Default for LinkCheckOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 8,
+            timeout: 8000,
+            same_origin_only: false
+        }
+    }
+}
+
+const COLLECT_LINKS_JS: &str = r##"
+(sameOrigin) => Array.from(new Set(
+    Array.from(document.querySelectorAll('a[href]'))
+        .map(a => a.href)
+        .filter(href => href.startsWith('http'))
+        .filter(href => !sameOrigin || new URL(href).origin === location.origin)
+        .map(href => href.split('#')[0])
+))
+"##;
+
+const CHECK_LINKS_JS: &str = r##"
+async (urls, concurrency, timeoutMs) => {
+    const check = async (url) => {
+        const started = performance.now();
+        const request = async (method, mode) => {
+            const controller = new AbortController();
+            const timer = setTimeout(() => controller.abort(), timeoutMs);
+            try {
+                return await fetch(url, { method, mode, redirect: 'follow', signal: controller.signal });
+            } finally {
+                clearTimeout(timer);
+            }
+        };
+        const result = { url, status: null, redirectedTo: null, loadTime: 0, opaque: false, error: null };
+        try {
+            let res;
+            try {
+                res = await request('HEAD', 'cors');
+                if (res.status === 405) res = await request('GET', 'cors');
+            } catch (e) {
+                if (e.name === 'AbortError') throw e;
+                res = await request('GET', 'no-cors');
+            }
+            result.opaque = res.type === 'opaque';
+            result.status = result.opaque ? null : res.status;
+            result.redirectedTo = res.redirected ? res.url : null;
+        } catch (e) {
+            result.error = e.name === 'AbortError' ? 'timeout' : String(e);
+        }
+        result.loadTime = performance.now() - started;
+        return result;
+    };
+    const results = new Array(urls.length);
+    let next = 0;
+    const worker = async () => {
+        while (next < urls.length) {
+            const idx = next++;
+            results[idx] = await check(urls[idx]);
+        }
+    };
+    await Promise.all(Array.from({ length: Math.max(1, concurrency) }, worker));
+    return results;
+}
+"##;
+
+pub async fn collect_links(page: &Page, same_origin_only: bool) -> Result<Vec<String>, BrowserError> {
+    page.evaluate(
+        format!("({COLLECT_LINKS_JS})({same_origin_only})")
+    ).await?
+        .into_value()
+        .map_err(|_| BrowserError::Serialization)
+}
+
+pub async fn check_urls(page: &Page, urls: &[String], options: &LinkCheckOptions) -> Result<Vec<LinkStatus>, BrowserError> {
+    let urls = serde_json::to_string(urls)
+        .map_err(|_| BrowserError::Serialization)?;
+    page.evaluate(
+        format!(
+            "({CHECK_LINKS_JS})({urls}, {}, {})",
+            options.concurrency, options.timeout
+        )
+    ).await?
+        .into_value()
+        .map_err(|_| BrowserError::Serialization)
+}
+
+pub async fn check_links(page: &Page, options: &LinkCheckOptions) -> Result<Vec<LinkStatus>, BrowserError> {
+    let urls = collect_links(page, options.same_origin_only).await?;
+    check_urls(page, &urls, options).await
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeoReport {
+    pub title: Option<String>,
+    pub title_length: usize,
+    pub description: Option<String>,
+    pub description_length: usize,
+    pub h1_count: usize,
+    pub canonical: Option<String>,
+    pub robots: Option<String>,
+    pub lang: Option<String>,
+    pub structured_data: bool,
+    pub open_graph: bool,
+    pub images_without_alt: usize,
+    pub broken_images: Vec<String>,
+}
+
+impl SeoReport {
+    pub fn issues(&self) -> Vec<&'static str> {
+        let mut issues = Vec::new();
+        if self.title.is_none() {
+            issues.push("missing title");
+        } else if !(10..=60).contains(&self.title_length) {
+            issues.push("title length out of range");
+        }
+        if self.description.is_none() {
+            issues.push("missing meta description");
+        } else if !(50..=160).contains(&self.description_length) {
+            issues.push("description length out of range");
+        }
+        if self.h1_count != 1 {
+            issues.push("expected exactly one h1");
+        }
+        if self.canonical.is_none() {
+            issues.push("missing canonical link");
+        }
+        if self.robots.as_deref().map(|r| r.contains("noindex")).unwrap_or(false) {
+            issues.push("page is noindex");
+        }
+        if !self.broken_images.is_empty() {
+            issues.push("broken images");
+        }
+        issues
+    }
+}
+
+const SEO_AUDIT_JS: &str = r##"
+(() => {
+    const meta = (name) => {
+        const el = document.querySelector(`meta[name="${name}" i]`);
+        return el ? el.getAttribute('content') : null;
+    };
+    const title = document.title || null;
+    const description = meta('description');
+    const canonical = document.querySelector('link[rel="canonical"]');
+    const images = Array.from(document.images);
+    return {
+        title,
+        titleLength: title ? title.length : 0,
+        description,
+        descriptionLength: description ? description.length : 0,
+        h1Count: document.querySelectorAll('h1').length,
+        canonical: canonical ? canonical.href : null,
+        robots: meta('robots'),
+        lang: document.documentElement.getAttribute('lang'),
+        structuredData: !!document.querySelector('script[type="application/ld+json"], [itemscope]'),
+        openGraph: !!document.querySelector('meta[property^="og:"]'),
+        imagesWithoutAlt: images.filter(img => !img.hasAttribute('alt')).length,
+        brokenImages: images
+            .filter(img => img.complete && img.naturalWidth === 0 && img.src)
+            .map(img => img.src)
+    };
+})()
+"##;
+
+pub async fn seo_audit(page: &Page) -> Result<SeoReport, BrowserError> {
+    page.evaluate(SEO_AUDIT_JS).await?
+        .into_value()
+        .map_err(|_| BrowserError::Serialization)
+}
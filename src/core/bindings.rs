@@ -0,0 +1,113 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tokio_stream::{Stream, StreamExt};
+use chromiumoxide::{
+    cdp::js_protocol::runtime::{AddBindingParams, EventBindingCalled},
+    Page
+};
+
+use crate::error::BrowserError;
+use super::hooks::catch_panic;
+
+
+static BINDING_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+pub(crate) fn unique_binding_name(prefix: &str) -> String {
+    format!("__bb_{prefix}_{}", BINDING_COUNTER.fetch_add(1, Ordering::Relaxed))
+}
+
+pub(crate) async fn binding_payloads(page: &Page, name: &str) -> Result<impl Stream<Item = String>, BrowserError> {
+    let events = page.event_listener::<EventBindingCalled>().await?;
+    page.execute(AddBindingParams::new(name)).await?;
+    let name = name.to_owned();
+    Ok(events.filter_map(move |e| (e.name == name).then(|| e.payload.clone())))
+}
+
+pub(crate) async fn inject_script(page: &Page, script: String) -> Result<(), BrowserError> {
+    page.evaluate_on_new_document(script.as_str()).await?;
+    page.evaluate(script).await?;
+    Ok(())
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MutationEvent {
+    pub kind: String,
+    pub target: String,
+    pub text: Option<String>,
+    pub attribute: Option<String>,
+}
+
+const MUTATION_OBSERVER_JS: &str = r##"
+(() => {
+    const selector = __SELECTOR__;
+    const send = window[__BINDING__];
+    const describe = (el) => el.tagName.toLowerCase()
+        + (el.id ? '#' + el.id : '')
+        + (el.classList.length ? '.' + Array.from(el.classList).join('.') : '');
+    const start = () => new MutationObserver(mutations => {
+        for (const m of mutations) {
+            const el = m.target.nodeType === Node.ELEMENT_NODE ? m.target : m.target.parentElement;
+            if (!el || !el.closest(selector)) continue;
+            send(JSON.stringify({
+                kind: m.type,
+                target: describe(el),
+                text: el.textContent,
+                attribute: m.attributeName
+            }));
+        }
+    }).observe(document.documentElement, {
+        childList: true, subtree: true, characterData: true, attributes: true
+    });
+    if (document.documentElement) start();
+    else document.addEventListener('DOMContentLoaded', start);
+})()
+"##;
+
+pub async fn watch_mutations(page: &Page, selector: &str) -> Result<impl Stream<Item = MutationEvent>, BrowserError> {
+    let name = unique_binding_name("mutations");
+    let payloads = binding_payloads(page, &name).await?;
+    let selector = serde_json::to_string(selector)
+        .map_err(|_| BrowserError::Serialization)?;
+    inject_script(
+        page,
+        MUTATION_OBSERVER_JS
+            .replace("__SELECTOR__", &selector)
+            .replace("__BINDING__", &format!("'{name}'"))
+    ).await?;
+
+    Ok(payloads.filter_map(|p| serde_json::from_str(&p).ok()))
+}
+
+const EXPOSE_FUNCTION_JS: &str = r##"
+(() => {
+    const send = window[__BINDING__];
+    window[__NAME__] = (...args) => send(JSON.stringify(args));
+})()
+"##;
+
+pub async fn page_expose_function<F>(page: &Page, name: &str, handler: F) -> Result<JoinHandle<()>, BrowserError>
+where
+    F: Fn(Vec<serde_json::Value>) + Send + 'static
+{
+    let binding = unique_binding_name("expose");
+    let mut payloads = Box::pin(binding_payloads(page, &binding).await?);
+    let name = serde_json::to_string(name)
+        .map_err(|_| BrowserError::Serialization)?;
+    inject_script(
+        page,
+        EXPOSE_FUNCTION_JS
+            .replace("__NAME__", &name)
+            .replace("__BINDING__", &format!("'{binding}'"))
+    ).await?;
+
+    Ok (
+        tokio::task::spawn(async move {
+            while let Some(payload) = payloads.next().await {
+                if let Ok(args) = serde_json::from_str(&payload) {
+                    let _ = catch_panic(|| handler(args));
+                }
+            }
+        })
+    )
+}
@@ -1,17 +1,33 @@
 //#![warn(missing_docs)]
-use std::time::Duration;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::Duration
+};
 use serde::{Deserialize, Serialize};
 use tokio_stream::StreamExt;
 use tokio::{
+    sync::Mutex,
     task::JoinHandle,
     time::{sleep, timeout}
 };
 use chromiumoxide::{
     cdp::browser_protocol::{
         //emulation::SetGeolocationOverrideParams,
-        network::CookieParam
+        browser::BrowserContextId,
+        network::{
+            CookieParam,
+            EventResponseReceived,
+            Headers,
+            ResourceType,
+            SetCacheDisabledParams,
+            SetExtraHttpHeadersParams
+        },
+        page::{NavigateParams, ReferrerPolicy},
+        target::CreateTargetParams
     },
     browser::HeadlessMode,
+    detection::{default_executable, DetectionOptions},
     Browser,
     BrowserConfig,
     Page
@@ -19,7 +35,17 @@ use chromiumoxide::{
 use rand::Rng;
 
 pub use crate::error::BrowserError;
+use super::cancel::{cancellable, close_pages, CancellationToken};
 use super::extension;
+use super::flags::ChromeFlags;
+use super::fingerprint::FingerprintProfile;
+use super::config::EffectiveConfig;
+use super::context::{split_proxy, ContextRegistry};
+use super::intercept::{InterceptConfig, Interceptor, ProxyAuth, TRACKER_BLOCKLIST};
+use super::network::MainResponse;
+use super::warmup::WarmupPlan;
+use super::hooks::PageHooks;
+use super::proxy::IpCache;
 
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -29,18 +55,63 @@ pub struct MyIP {
     pub cc: String,
 }
 
-pub static DEFAULT_ARGS: [&str; 8] = [
-    "--disable-blink-features=AutomationControlled",
-    "--disable-default-apps",
-    "--no-first-run",
-    "--disable-sync",
-    "--lang=en_US",
-    "--no-default-browser-check",
-    "--disable-smooth-scrolling",
-    "--disable-features=TranslateUI"
-];
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct IpProvider {
+    pub url: String,
+    pub ip_field: String,
+    pub country_field: String,
+    pub cc_field: String,
+}
 
-#[derive(Clone, Debug)]
+impl IpProvider {
+    pub fn new(url: &str, ip_field: &str, country_field: &str, cc_field: &str) -> Self {
+        Self {
+            url: url.into(),
+            ip_field: ip_field.into(),
+            country_field: country_field.into(),
+            cc_field: cc_field.into()
+        }
+    }
+
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::new("https://api.myip.com/", "ip", "country", "cc"),
+            Self::new("https://ipwho.is/", "ip", "country", "country_code"),
+            Self::new("https://ipapi.co/json/", "ip", "country_name", "country_code")
+        ]
+    }
+
+    pub fn parse(&self, body: &str) -> Result<MyIP, BrowserError> {
+        let snippet: String = body.trim().chars().take(200).collect();
+        let value: serde_json::Value = serde_json::from_str(body.trim())
+            .map_err(|_| BrowserError::IpLookup(
+                format!("{}: non-JSON response: {snippet}", self.url)
+            ))?;
+        let field = |name: &str| value.get(name)
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_owned())
+            .ok_or_else(|| BrowserError::IpLookup(
+                format!("{}: missing field `{name}`: {snippet}", self.url)
+            ));
+
+        let ip = field(&self.ip_field)?;
+        if ip.parse::<std::net::IpAddr>().is_err() {
+            return Err(BrowserError::IpLookup(
+                format!("{}: `{ip}` is not an IP address: {snippet}", self.url)
+            ));
+        }
+
+        Ok (
+            MyIP {
+                ip,
+                country: field(&self.country_field).unwrap_or_default(),
+                cc: field(&self.cc_field).unwrap_or_default()
+            }
+        )
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct BrowserTimings {
     pub launch_sleep: u64,
     pub set_proxy_sleep: u64,
@@ -59,9 +130,17 @@ impl Default for BrowserTimings {
     }
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum CacheMode {
+    #[default]
+    Default,
+    Bypass,
+}
+
 #[derive(Clone, Debug)]
 pub struct PageParam<'a> {
     pub proxy: Option<&'a str>,
+    pub direct: bool,
     pub wait_for_el: Option<(&'a str, u64)>,
     pub wait_for_el_until: Option<(&'a str, &'a str, u64)>,
     pub user_agent: Option<&'a str>,
@@ -69,6 +148,13 @@ pub struct PageParam<'a> {
     //pub geolocation: Option<(f64, f64)>,
     pub wait_open_on_page: Option<u64>,
     pub wait_for_navigation: Option<u64>,
+    pub block_resources: Vec<ResourceType>,
+    pub url_blocklist: Vec<&'a str>,
+    pub extra_headers: Vec<(&'a str, &'a str)>,
+    pub init_scripts: Vec<&'a str>,
+    pub cache_mode: CacheMode,
+    pub referrer: Option<&'a str>,
+    pub referrer_policy: Option<ReferrerPolicy>,
     pub duration: u64
 }
 
@@ -76,6 +162,7 @@ impl<'a> Default for PageParam<'a> {
     fn default() -> Self {
         Self {
             proxy: None,
+            direct: false,
             wait_for_el: None,
             wait_for_el_until: None,
             user_agent: None,
@@ -83,25 +170,236 @@ impl<'a> Default for PageParam<'a> {
             //geolocation: None,
             wait_open_on_page: None,
             wait_for_navigation: None,
+            block_resources: Vec::new(),
+            url_blocklist: Vec::new(),
+            extra_headers: Vec::new(),
+            init_scripts: Vec::new(),
+            cache_mode: CacheMode::Default,
+            referrer: None,
+            referrer_policy: None,
             duration: 0
         }
     }
 }
 
+impl<'a> PageParam<'a> {
+    pub fn block_resources(mut self, types: &[ResourceType]) -> Self {
+        self.block_resources.extend_from_slice(types);
+        self
+    }
+
+    pub fn url_blocklist(mut self, patterns: &[&'a str]) -> Self {
+        self.url_blocklist.extend_from_slice(patterns);
+        self
+    }
+
+    pub fn block_trackers(self) -> Self {
+        self.url_blocklist(&TRACKER_BLOCKLIST)
+    }
+
+    pub(crate) fn layer(&self, over: &PageParam<'a>) -> PageParam<'a> {
+        let mut layered = over.clone();
+        layered.user_agent = over.user_agent.or(self.user_agent);
+        layered.block_resources = [self.block_resources.as_slice(), &over.block_resources].concat();
+        layered.url_blocklist = [self.url_blocklist.as_slice(), &over.url_blocklist].concat();
+        layered.extra_headers = [self.extra_headers.as_slice(), &over.extra_headers].concat();
+        layered.init_scripts = [self.init_scripts.as_slice(), &over.init_scripts].concat();
+        if over.cache_mode == CacheMode::Default {
+            layered.cache_mode = self.cache_mode;
+        }
+        layered
+    }
+
+    pub fn navigate_params(&self, url: &str) -> NavigateParams {
+        NavigateParams {
+            referrer: self.referrer.map(|r| r.into()),
+            referrer_policy: self.referrer_policy.clone(),
+            ..NavigateParams::new(url)
+        }
+    }
+
+    pub(crate) async fn setup_page(&self, page: &Page, proxy_auth: Option<ProxyAuth>) -> Result<(), BrowserError> {
+        if let Some(user_agent) = self.user_agent {
+            page.set_user_agent(user_agent).await?;
+        }
+        if self.cache_mode == CacheMode::Bypass {
+            page.execute(SetCacheDisabledParams::new(true)).await?;
+        }
+        if !self.extra_headers.is_empty() {
+            let headers: serde_json::Map<String, serde_json::Value> = self.extra_headers.iter()
+                .map(|(name, value)| (name.to_string(), (*value).into()))
+                .collect();
+            page.execute(
+                SetExtraHttpHeadersParams::new(Headers::new(headers))
+            ).await?;
+        }
+        for script in &self.init_scripts {
+            page.evaluate_on_new_document(*script).await?;
+        }
+        let intercept = InterceptConfig {
+            proxy_auth,
+            ..InterceptConfig::from(self)
+        };
+        if intercept.is_active() {
+            Interceptor::start(page, intercept).await?.detach();
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PageParamOwned {
+    pub proxy: Option<String>,
+    pub direct: bool,
+    pub wait_for_el: Option<(String, u64)>,
+    pub wait_for_el_until: Option<(String, String, u64)>,
+    pub user_agent: Option<String>,
+    pub cookies: Vec<CookieParam>,
+    pub wait_open_on_page: Option<u64>,
+    pub wait_for_navigation: Option<u64>,
+    pub block_resources: Vec<ResourceType>,
+    pub url_blocklist: Vec<String>,
+    pub extra_headers: Vec<(String, String)>,
+    pub init_scripts: Vec<String>,
+    pub cache_mode: CacheMode,
+    pub referrer: Option<String>,
+    pub referrer_policy: Option<ReferrerPolicy>,
+    pub duration: u64
+}
+
+impl PageParamOwned {
+    pub fn as_param(&self) -> PageParam<'_> {
+        PageParam::from(self)
+    }
+}
+
+impl<'a> From<&'a PageParamOwned> for PageParam<'a> {
+    fn from(owned: &'a PageParamOwned) -> Self {
+        Self {
+            proxy: owned.proxy.as_deref(),
+            direct: owned.direct,
+            wait_for_el: owned.wait_for_el.as_ref()
+                .map(|(s, t)| (s.as_str(), *t)),
+            wait_for_el_until: owned.wait_for_el_until.as_ref()
+                .map(|(s, u, t)| (s.as_str(), u.as_str(), *t)),
+            user_agent: owned.user_agent.as_deref(),
+            cookies: owned.cookies.clone(),
+            wait_open_on_page: owned.wait_open_on_page,
+            wait_for_navigation: owned.wait_for_navigation,
+            block_resources: owned.block_resources.clone(),
+            url_blocklist: owned.url_blocklist.iter()
+                .map(|p| p.as_str())
+                .collect(),
+            extra_headers: owned.extra_headers.iter()
+                .map(|(n, v)| (n.as_str(), v.as_str()))
+                .collect(),
+            init_scripts: owned.init_scripts.iter()
+                .map(|s| s.as_str())
+                .collect(),
+            cache_mode: owned.cache_mode,
+            referrer: owned.referrer.as_deref(),
+            referrer_policy: owned.referrer_policy.clone(),
+            duration: owned.duration
+        }
+    }
+}
+
+impl<'a> From<&PageParam<'a>> for PageParamOwned {
+    fn from(param: &PageParam<'a>) -> Self {
+        Self {
+            proxy: param.proxy.map(|p| p.into()),
+            direct: param.direct,
+            wait_for_el: param.wait_for_el
+                .map(|(s, t)| (s.into(), t)),
+            wait_for_el_until: param.wait_for_el_until
+                .map(|(s, u, t)| (s.into(), u.into(), t)),
+            user_agent: param.user_agent.map(|u| u.into()),
+            cookies: param.cookies.clone(),
+            wait_open_on_page: param.wait_open_on_page,
+            wait_for_navigation: param.wait_for_navigation,
+            block_resources: param.block_resources.clone(),
+            url_blocklist: param.url_blocklist.iter()
+                .map(|p| p.to_string())
+                .collect(),
+            extra_headers: param.extra_headers.iter()
+                .map(|(n, v)| (n.to_string(), v.to_string()))
+                .collect(),
+            init_scripts: param.init_scripts.iter()
+                .map(|s| s.to_string())
+                .collect(),
+            cache_mode: param.cache_mode,
+            referrer: param.referrer.map(|r| r.into()),
+            referrer_policy: param.referrer_policy.clone(),
+            duration: param.duration
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct MediaOptions {
+    pub mute_audio: bool,
+    pub block_autoplay: bool,
+    pub fake_media_devices: bool,
+    pub auto_accept_media_permissions: bool,
+}
+
+impl MediaOptions {
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.mute_audio {
+            args.push("--mute-audio".into());
+        }
+        if self.block_autoplay {
+            args.push("--autoplay-policy=document-user-activation-required".into());
+        }
+        if self.fake_media_devices {
+            args.push("--use-fake-device-for-media-stream".into());
+        }
+        if self.auto_accept_media_permissions {
+            args.push("--use-fake-ui-for-media-stream".into());
+        }
+        args
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ControlPages {
+    Foreground,
+    Background,
+    Disabled,
+}
+
 #[derive(Clone, Debug)]
 pub struct BrowserSessionConfig {
     pub executable: Option<String>,
     pub user_data_dir: Option<String>,
+    pub flags: ChromeFlags,
     pub args: Vec<String>,
     pub headless: HeadlessMode,
-    pub sandbox: bool,
     pub extensions: Vec<String>,
+    pub builtin_extension: bool,
+    pub control_pages: ControlPages,
     pub incognito: bool,
     pub port: u16,
     pub launch_timeout: u64,
     pub request_timeout: u64,
     pub cache_enabled: bool,
+    pub media: MediaOptions,
     pub timings: BrowserTimings,
+    pub warmup: Option<WarmupPlan>,
+    pub default_page_param: PageParam<'static>,
+    pub ip_cache_ttl: u64,
+    pub ip_providers: Vec<IpProvider>,
+    pub fingerprint: Option<FingerprintProfile>,
+    pub env: HashMap<String, String>,
+    // Relative paths in this config resolve against `base_dir`.
+    pub base_dir: Option<String>,
+    // Working directory of the spawned Chrome process. chromiumoxide has no
+    // `current_dir` option, so Chrome is started through a launcher script
+    // that changes into `cwd` first (unix only).
+    pub cwd: Option<String>,
+    pub ssl_key_log: Option<String>,
 }
 
 impl Default for BrowserSessionConfig {
@@ -109,18 +407,28 @@ impl Default for BrowserSessionConfig {
         Self {
             executable: None,
             user_data_dir: None,
-            args: DEFAULT_ARGS.into_iter()
-                .map(|v| v.into())
-                .collect(),
+            flags: ChromeFlags::default(),
+            args: Vec::new(),
             headless: HeadlessMode::False,
-            sandbox: false,
             extensions: Vec::new(),
+            builtin_extension: true,
+            control_pages: ControlPages::Foreground,
             incognito: false,
             port: 0,
             launch_timeout: 1500,
             request_timeout: 2000,
             cache_enabled: true,
+            media: MediaOptions::default(),
             timings: BrowserTimings::default(),
+            warmup: None,
+            default_page_param: PageParam::default(),
+            ip_cache_ttl: 300000,
+            ip_providers: IpProvider::defaults(),
+            fingerprint: None,
+            env: HashMap::new(),
+            base_dir: None,
+            cwd: None,
+            ssl_key_log: None,
         }
     }
 }
@@ -129,18 +437,62 @@ pub trait FromSessionConfig {
     fn to_config(&self) -> Result<BrowserConfig, BrowserError>;
 }
 
-impl FromSessionConfig for BrowserSessionConfig {
-    fn to_config(&self) -> Result<BrowserConfig, BrowserError> {
+impl BrowserSessionConfig {
+    pub fn resolve_path(&self, path: &str) -> String {
+        match &self.base_dir {
+            Some(base_dir) if Path::new(path).is_relative() => Path::new(base_dir)
+                .join(path)
+                .to_string_lossy()
+                .into_owned(),
+            _ => path.to_owned()
+        }
+    }
+
+    pub fn ssl_key_log_path(&self) -> Option<String> {
+        let path = self.resolve_path(self.ssl_key_log.as_deref()?);
+        Some(
+            std::path::absolute(&path)
+                .map(|p| p.to_string_lossy().into_owned())
+                .unwrap_or(path)
+        )
+    }
+
+    pub fn cwd_path(&self) -> Option<String> {
+        let path = self.resolve_path(self.cwd.as_deref()?);
+        Some(
+            std::path::absolute(&path)
+                .map(|p| p.to_string_lossy().into_owned())
+                .unwrap_or(path)
+        )
+    }
+
+    pub fn extension_list(&self) -> Vec<String> {
         let mut extensions = Vec::new();
-        extensions.push(extension::PATH.clone());
-        extensions.extend_from_slice(
-            self.extensions.as_slice()
+        if self.builtin_extension {
+            extensions.push(extension::PATH.clone());
+        }
+        extensions.extend(
+            self.extensions.iter().map(|e| self.resolve_path(e))
         );
+        extensions
+    }
+
+    pub fn launch_args(&self) -> Vec<String> {
+        let mut args = self.flags.to_args();
+        args.extend_from_slice(&self.args);
+        args.extend(self.media.to_args());
+        args
+    }
+}
+
+impl FromSessionConfig for BrowserSessionConfig {
+    fn to_config(&self) -> Result<BrowserConfig, BrowserError> {
+        self.flags.validate(&self.args)?;
         let mut builder = BrowserConfig::builder()
             .disable_default_args()
             .headless_mode(self.headless)
-            .args(&self.args)
-            .extensions(extensions)
+            .args(self.launch_args())
+            .extensions(self.extension_list())
             .viewport(None)
             .port(self.port)
             .launch_timeout(
@@ -153,17 +505,38 @@ impl FromSessionConfig for BrowserSessionConfig {
         if self.incognito {
             builder = builder.incognito();
         }
-        if !self.sandbox {
+        if !self.flags.sandbox {
             builder = builder.no_sandbox();
         }
         if self.cache_enabled {
             builder = builder.enable_cache();
         }
         if let Some(user_data_dir) = &self.user_data_dir {
-            builder = builder.user_data_dir(user_data_dir);
+            builder = builder.user_data_dir(self.resolve_path(user_data_dir));
         }
-        if let Some(executable) = &self.executable {
-            builder = builder.chrome_executable(executable);
+        let executable = self.executable.as_deref()
+            .map(|executable| PathBuf::from(self.resolve_path(executable)));
+        if !self.env.is_empty() {
+            builder = builder.envs(self.env.clone());
+        }
+        match self.cwd_path() {
+            Some(cwd) => {
+                let chrome = match executable {
+                    Some(executable) => executable,
+                    None => default_executable(DetectionOptions::default())
+                        .map_err(|_| BrowserError::BuildBrowserConfigError)?
+                };
+                builder = builder
+                    .chrome_executable(chrome_launcher()?)
+                    .env(LAUNCHER_CWD_ENV, cwd)
+                    .env(LAUNCHER_CHROME_ENV, chrome.to_string_lossy());
+            }
+            None => if let Some(executable) = executable {
+                builder = builder.chrome_executable(executable);
+            }
+        }
+        if let Some(path) = self.ssl_key_log_path() {
+            builder = builder.env("SSLKEYLOGFILE", path);
         }
 
         builder.build()
@@ -171,32 +544,104 @@ impl FromSessionConfig for BrowserSessionConfig {
     }
 }
 
+const LAUNCHER_CWD_ENV: &str = "BROWSER_BRIDGE_CWD";
+const LAUNCHER_CHROME_ENV: &str = "BROWSER_BRIDGE_CHROME";
+const LAUNCHER_SCRIPT: &str = "#!/bin/sh\n\
+cd \"$BROWSER_BRIDGE_CWD\" || exit 1\n\
+exec \"$BROWSER_BRIDGE_CHROME\" \"$@\"\n";
+
+// The script takes its directory and executable from the environment, so its
+// content never changes and one copy serves every session.
+#[cfg(unix)]
+fn chrome_launcher() -> Result<PathBuf, BrowserError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = std::env::temp_dir().join("browser-bridge");
+    let path = dir.join("chrome-launcher.sh");
+    if std::fs::read_to_string(&path).is_ok_and(|s| s == LAUNCHER_SCRIPT) {
+        return Ok(path);
+    }
+    let write = || -> std::io::Result<()> {
+        std::fs::create_dir_all(&dir)?;
+        let tmp = dir.join(format!("chrome-launcher.{}.tmp", std::process::id()));
+        std::fs::write(&tmp, LAUNCHER_SCRIPT)?;
+        std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o755))?;
+        std::fs::rename(&tmp, &path)
+    };
+    write().map_err(|_| BrowserError::BuildBrowserConfigError)?;
+    Ok(path)
+}
+
+#[cfg(not(unix))]
+fn chrome_launcher() -> Result<PathBuf, BrowserError> {
+    Err(BrowserError::InvalidArgument("cwd is only supported on unix".into()))
+}
+
 pub struct BrowserSession {
     pub browser: Browser,
     pub handle: JoinHandle<()>,
     pub timings: BrowserTimings,
+    pub control_pages: ControlPages,
+    pub page_hooks: PageHooks,
+    pub default_page_param: PageParam<'static>,
+    pub proxy_contexts: Mutex<HashMap<String, BrowserContextId>>,
+    pub ip_cache: IpCache,
+    pub ip_providers: Vec<IpProvider>,
+    pub fingerprint: Option<FingerprintProfile>,
+    pub(crate) effective_config: EffectiveConfig,
+    pub(crate) contexts: ContextRegistry,
 }
 
 impl BrowserSession {
     pub async fn launch(bsc: BrowserSessionConfig) -> Result<Self, BrowserError> {
         let timings = bsc.timings.clone();
+        let control_pages = match bsc.builtin_extension {
+            true => bsc.control_pages,
+            false => ControlPages::Disabled
+        };
+        let mut effective_config = EffectiveConfig::resolve(&bsc);
         let (browser, mut handler) = Browser::launch(
             bsc.to_config()?
         ).await?;
         let handle = tokio::task::spawn(async move {
             while handler.next().await.is_some() {}
         });
+        if let Some(port) = browser.websocket_address()
+            .rsplit_once(':')
+            .and_then(|(_, rest)| rest.split('/').next())
+            .and_then(|port| port.parse().ok()) {
+            effective_config.port = port;
+        }
         sleep(
             Duration::from_millis(timings.launch_sleep)
         ).await;
+        let session = Self {
+            browser,
+            handle,
+            timings,
+            control_pages,
+            page_hooks: PageHooks::default(),
+            default_page_param: bsc.default_page_param.clone(),
+            proxy_contexts: Mutex::new(HashMap::new()),
+            ip_cache: IpCache::new(
+                Duration::from_millis(bsc.ip_cache_ttl)
+            ),
+            ip_providers: bsc.ip_providers.clone(),
+            fingerprint: bsc.fingerprint.clone(),
+            effective_config,
+            contexts: ContextRegistry::default()
+        };
+        if let Some(proxy) = session.default_page_param.proxy {
+            session.set_proxy(proxy).await?;
+        }
+        if !session.default_page_param.cookies.is_empty() {
+            session.set_cookies_global(session.default_page_param.cookies.clone()).await?;
+        }
+        if let Some(plan) = &bsc.warmup {
+            session.warm_up(plan).await?;
+        }
 
-        Ok (
-            Self {
-                browser,
-                handle,
-                timings
-            }
-        )
+        Ok(session)
     }
 
     pub async fn launch_with_default_config() -> Result<Self, BrowserError> {
@@ -208,6 +653,15 @@ impl BrowserSession {
         self.timings = timings;
     }
 
+    pub async fn chrome_major(&self) -> Result<u32, BrowserError> {
+        let product = self.browser.version().await?.product;
+        product.split('/')
+            .nth(1)
+            .and_then(|version| version.split('.').next())
+            .and_then(|major| major.parse().ok())
+            .ok_or(BrowserError::ChromeInternal)
+    }
+
     pub async fn close(&mut self) {
         if self.browser.close().await.is_err() {
             self.browser.kill().await;
@@ -218,19 +672,44 @@ impl BrowserSession {
                 attempts += 1;
             }
         }
+        self.page_hooks.stop();
+        self.contexts.stop();
         self.handle.abort();
     }
 
     pub async fn new_page(&self) -> Result<Page, BrowserError> {
-        let new_page = self.browser.new_page("about:blank").await?;
-        Ok(new_page)
+        self.new_page_with(&PageParam::default()).await
     }
 
-    pub async fn open_on_page<'a>(&self, url: &str, page: &'a Page) -> Result<(), BrowserError> {
+    pub(crate) async fn new_page_with(&self, param: &PageParam<'_>) -> Result<Page, BrowserError> {
+        let proxy = match param.direct {
+            true => Some("direct://"),
+            false => param.proxy
+        };
+        let Some(proxy) = proxy else {
+            if !param.cookies.is_empty() {
+                self.set_cookies_global(param.cookies.clone()).await?;
+            }
+            return self.new_page_in_context(None, param, None, None).await;
+        };
+        let context_id = self.proxy_context(proxy).await?;
+        if !param.cookies.is_empty() {
+            self.set_cookies_in_context(param.cookies.clone(), Some(context_id.clone())).await?;
+        }
+        self.new_page_in_context(
+            Some(context_id), param, split_proxy(proxy).1, None
+        ).await
+    }
+
+    pub async fn open_on_page(&self, url: &str, page: &Page) -> Result<(), BrowserError> {
+        self.navigate_on_page(NavigateParams::new(url), page).await
+    }
+
+    pub async fn navigate_on_page(&self, params: NavigateParams, page: &Page) -> Result<(), BrowserError> {
         //page.goto(url).await?;
         let _ = timeout(
             Duration::from_millis(self.timings.page_goto_timeout),
-            page.goto(url)
+            page.goto(params)
         ).await;
 
         Ok(())
@@ -243,6 +722,83 @@ impl BrowserSession {
         Ok(page)
     }
 
+    pub async fn open_batch(&self, urls: &[&str], max_tabs: usize) -> Result<Vec<Page>, BrowserError> {
+        self.open_batch_cancellable(urls, max_tabs, &CancellationToken::new()).await
+    }
+
+    pub async fn open_batch_cancellable(
+        &self,
+        urls: &[&str],
+        max_tabs: usize,
+        token: &CancellationToken
+    ) -> Result<Vec<Page>, BrowserError> {
+        let mut pages: Vec<Page> = Vec::with_capacity(urls.len());
+        for wave in urls.chunks(max_tabs.max(1)) {
+            let mut loads = Vec::with_capacity(wave.len());
+            for url in wave {
+                let page = match token.is_cancelled() {
+                    true => Err(BrowserError::Cancelled),
+                    false => self.new_page().await
+                };
+                let page = match page {
+                    Ok(page) => page,
+                    Err(e) => {
+                        loads.iter().for_each(|load: &JoinHandle<()>| load.abort());
+                        close_pages(pages).await;
+                        return Err(e);
+                    }
+                };
+                let params = NavigateParams::new(*url);
+                let goto_timeout = Duration::from_millis(self.timings.page_goto_timeout);
+                let tab = page.clone();
+                loads.push(tokio::task::spawn(async move {
+                    let _ = timeout(goto_timeout, tab.goto(params)).await;
+                }));
+                pages.push(page);
+            }
+            let waited = cancellable(token, async {
+                for load in loads.iter_mut() {
+                    let _ = load.await;
+                }
+                Ok(())
+            }).await;
+            if let Err(e) = waited {
+                loads.iter().for_each(|load| load.abort());
+                close_pages(pages).await;
+                return Err(e);
+            }
+        }
+
+        Ok(pages)
+    }
+
+    pub async fn open_with_response(&self, url: &str) -> Result<(Page, MainResponse), BrowserError> {
+        let page = self.new_page().await?;
+        self.open_page_for_response(url, page).await
+    }
+
+    pub(crate) async fn open_page_for_response(&self, url: &str, page: Page) -> Result<(Page, MainResponse), BrowserError> {
+        let mut responses = page.event_listener::<EventResponseReceived>().await?;
+        self.open_on_page(url, &page).await?;
+        let main_frame = page.mainframe().await?;
+        let mut main_response = None;
+        while let Ok(Some(event)) = timeout(
+            Duration::from_millis(self.timings.action_sleep),
+            responses.next()
+        ).await {
+            if event.r#type == ResourceType::Document && event.frame_id == main_frame {
+                main_response = Some(MainResponse::from(&event.response));
+            }
+        }
+        match main_response {
+            Some(response) => Ok((page, response)),
+            None => {
+                let _ = page.close().await;
+                Err(BrowserError::Navigation)
+            }
+        }
+    }
+
     pub async fn open_with_duration(&self, url: &str, duration: u64) -> Result<Page, BrowserError> {
         let page = self.new_page().await?;
         self.open_on_page(url, &page).await?;
@@ -254,16 +810,7 @@ impl BrowserSession {
     }
 
     pub async fn open_with_param<'a>(&self, url: &str, param: &PageParam<'a>) -> Result<Page, BrowserError> {
-        if let Some(proxy) = param.proxy {
-            self.set_proxy(proxy).await?;
-        }
-        let page = self.new_page().await?;
-        if let Some(user_agent) = param.user_agent {
-            page.set_user_agent(user_agent).await?;
-        }
-        if !param.cookies.is_empty() {
-            page.set_cookies(param.cookies.clone()).await?;
-        }
+        let page = self.new_page_with(param).await?;
         /*
         if let Some(geolocation) = param.geolocation {
             page.emulate_geolocation(
@@ -275,13 +822,13 @@ impl BrowserSession {
             ).await?;
         }
          */
-        if param.wait_open_on_page.is_some() {
+        if let Some(wait_open) = param.wait_open_on_page {
             let _ = timeout(
-                Duration::from_millis(param.wait_open_on_page.unwrap()),
-                self.open_on_page(url, &page)
+                Duration::from_millis(wait_open),
+                self.navigate_on_page(param.navigate_params(url), &page)
             ).await;
         } else {
-            self.open_on_page(url, &page).await?;
+            self.navigate_on_page(param.navigate_params(url), &page).await?;
         }
         if let Some(wait_timeout) = param.wait_for_navigation {
             let _ = timeout(
@@ -306,9 +853,19 @@ impl BrowserSession {
         Ok(page)
     }
 
-    pub async fn set_proxy(&self, proxy: &str) -> Result<(), BrowserError> {
-        if let Err(e) = self.browser
-        .new_page(format!("chrome://set_proxy/{proxy}")).await {
+    async fn send_command(&self, url: String, wait: u64) -> Result<(), BrowserError> {
+        let params = match self.control_pages {
+            ControlPages::Disabled => {
+                return Err(BrowserError::ControlPagesDisabled);
+            },
+            ControlPages::Foreground => CreateTargetParams::new(url),
+            ControlPages::Background => CreateTargetParams::builder()
+                .url(url)
+                .background(true)
+                .build()
+                .map_err(|_| BrowserError::PageCreation)?
+        };
+        if let Err(e) = self.browser.new_page(params).await {
             let error = BrowserError::from(e);
             match error {
                 BrowserError::NetworkIO => {},
@@ -316,67 +873,92 @@ impl BrowserSession {
             }
         }
         sleep(
-            Duration::from_millis(self.timings.set_proxy_sleep)
+            Duration::from_millis(wait)
         ).await;
         Ok(())
     }
 
+    pub async fn set_proxy(&self, proxy: &str) -> Result<(), BrowserError> {
+        self.send_command(
+            format!("chrome://set_proxy/{proxy}"),
+            self.timings.set_proxy_sleep
+        ).await?;
+        self.ip_cache.set_active_proxy(Some(proxy));
+        Ok(())
+    }
+
     pub async fn reset_proxy(&self) -> Result<(), BrowserError> {
-        if let Err(e) = self.browser
-        .new_page("chrome://reset_proxy").await {
-            let error = BrowserError::from(e);
-            match error {
-                BrowserError::NetworkIO => {},
-                _ => { return Err(error); }
-            }
-        }
-        sleep(
-            Duration::from_millis(self.timings.action_sleep)
-        ).await;
+        self.send_command(
+            "chrome://reset_proxy".into(),
+            self.timings.action_sleep
+        ).await?;
+        self.ip_cache.set_active_proxy(None);
         Ok(())
     }
 
+    pub async fn set_proxy_bypass(&self, hosts: &[&str]) -> Result<(), BrowserError> {
+        self.send_command(
+            format!("chrome://proxy_bypass/?hosts={}", hosts.join(",")),
+            self.timings.action_sleep
+        ).await
+    }
+
     pub async fn close_tabs(&self) -> Result<(), BrowserError> {
-        if let Err(e) = self.browser
-        .new_page("chrome://close_tabs").await {
-            let error = BrowserError::from(e);
-            match error {
-                BrowserError::NetworkIO => {},
-                _ => { return Err(error); }
-            }
-        }
-        sleep(
-            Duration::from_millis(self.timings.action_sleep)
-        ).await;
-        Ok(())
+        self.send_command(
+            "chrome://close_tabs".into(),
+            self.timings.action_sleep
+        ).await
     }
 
     pub async fn clear_data(&self) -> Result<(), BrowserError> {
-        if let Err(e) = self.browser
-        .new_page("chrome://clear_data").await {
-            let error = BrowserError::from(e);
-            match error {
-                BrowserError::NetworkIO => {},
-                _ => { return Err(error); }
-            }
-        }
-        sleep(
-            Duration::from_millis(self.timings.action_sleep)
-        ).await;
-        Ok(())
+        self.send_command(
+            "chrome://clear_data".into(),
+            self.timings.action_sleep
+        ).await
     }
 
-    pub async fn myip(&self) -> Result<MyIP, BrowserError> {
-        let page = self.open("https://api.myip.com/").await?;
-        let myip = page.find_element("body").await?
-            .inner_text().await?
-            .ok_or(BrowserError::Serialization)
-            .map(|s|
-                serde_json::from_str(&s)
-                .map_err(|_| BrowserError::Serialization)
-            )?;
+    async fn myip_from(&self, provider: &IpProvider, param: &PageParam<'_>) -> Result<MyIP, BrowserError> {
+        let page = self.new_page_with(param).await?;
+        self.open_on_page(&provider.url, &page).await?;
+        let body = match page.find_element("body").await {
+            Ok(el) => el.inner_text().await,
+            Err(e) => Err(e)
+        };
         let _ = page.close().await;
-        myip
+        provider.parse(&body?.unwrap_or_default())
+    }
+
+    pub async fn myip(&self) -> Result<MyIP, BrowserError> {
+        self.lookup_ip(false).await
+    }
+
+    pub async fn myip_direct(&self) -> Result<MyIP, BrowserError> {
+        self.lookup_ip(true).await
+    }
+
+    async fn lookup_ip(&self, direct: bool) -> Result<MyIP, BrowserError> {
+        let proxy = match direct {
+            true => None,
+            false => self.ip_cache.active_proxy()
+        };
+        let param = PageParam {
+            direct,
+            ..Default::default()
+        };
+        let ip = self.myip_with(&param).await?;
+        self.ip_cache.insert(proxy, ip.clone());
+        Ok(ip)
+    }
+
+    pub(crate) async fn myip_with(&self, param: &PageParam<'_>) -> Result<MyIP, BrowserError> {
+        let mut error = BrowserError::IpLookup("no ip providers configured".into());
+        for provider in &self.ip_providers {
+            match self.myip_from(provider, param).await {
+                Ok(ip) => return Ok(ip),
+                Err(e) => error = e
+            }
+        }
+        Err(error)
     }
 }
 
@@ -467,3 +1049,67 @@ pub fn random_user_agent() -> &'static str {
     let index = rng.gen_range(0..USER_AGENT_LIST.len());
     USER_AGENT_LIST[index]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider() -> IpProvider {
+        IpProvider::new("https://ipwho.is/", "ip", "country", "country_code")
+    }
+
+    #[test]
+    fn parses_provider_fields() {
+        let ip = provider().parse(
+            " {\"ip\":\"2001:db8::1\",\"country\":\"Germany\",\"country_code\":\"DE\"}\n"
+        ).unwrap();
+        assert_eq!((ip.ip.as_str(), ip.country.as_str(), ip.cc.as_str()), ("2001:db8::1", "Germany", "DE"));
+    }
+
+    #[test]
+    fn country_fields_are_optional() {
+        let ip = provider().parse(r#"{"ip":"203.0.113.7","country_code":null}"#).unwrap();
+        assert_eq!(ip.ip, "203.0.113.7");
+        assert!(ip.country.is_empty() && ip.cc.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn launcher_runs_chrome_in_cwd() {
+        let cwd = std::env::temp_dir().canonicalize().unwrap();
+        let output = std::process::Command::new(chrome_launcher().unwrap())
+            .env(LAUNCHER_CWD_ENV, &cwd)
+            .env(LAUNCHER_CHROME_ENV, "/bin/sh")
+            .args(["-c", "pwd; echo \"$1\"", "sh", "--flag=a b"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            format!("{}\n--flag=a b\n", cwd.display())
+        );
+    }
+
+    #[test]
+    fn cwd_resolves_against_base_dir() {
+        let config = BrowserSessionConfig {
+            base_dir: Some("/srv/jobs".into()),
+            cwd: Some("run".into()),
+            ..Default::default()
+        };
+        assert_eq!(config.cwd_path().as_deref(), Some("/srv/jobs/run"));
+    }
+
+    #[test]
+    fn rejects_unusable_responses() {
+        for body in [
+            "<html>Too Many Requests</html>",
+            r#"{"success":false,"message":"Reserved range"}"#,
+            r#"{"ip":""}"#,
+            r#"{"ip":"rate limited"}"#,
+            r#"{"ip":42}"#
+        ] {
+            assert!(matches!(provider().parse(body), Err(BrowserError::IpLookup(_))), "{body}");
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use std::future::Future;
+use chromiumoxide::Page;
+pub use tokio_util::sync::CancellationToken;
+
+use crate::error::BrowserError;
+use super::browser::BrowserSession;
+use super::context::BrowserContextHandle;
+use super::hooks::isolate;
+use super::pool::{BrowserPool, PooledPage};
+
+
+pub async fn cancellable<T>(
+    token: &CancellationToken,
+    fut: impl Future<Output = Result<T, BrowserError>>
+) -> Result<T, BrowserError> {
+    tokio::select! {
+        biased;
+        _ = token.cancelled() => Err(BrowserError::Cancelled),
+        result = fut => result
+    }
+}
+
+pub(crate) async fn close_pages(pages: Vec<Page>) {
+    for page in pages {
+        let _ = page.close().await;
+    }
+}
+
+impl BrowserSession {
+    pub async fn open_cancellable(&self, url: &str, token: &CancellationToken) -> Result<Page, BrowserError> {
+        if token.is_cancelled() {
+            return Err(BrowserError::Cancelled);
+        }
+        let page = cancellable(token, self.new_page()).await?;
+        if let Err(e) = cancellable(token, self.open_on_page(url, &page)).await {
+            let _ = page.close().await;
+            return Err(e);
+        }
+
+        Ok(page)
+    }
+}
+
+impl BrowserContextHandle<'_> {
+    pub async fn open_cancellable(&self, url: &str, token: &CancellationToken) -> Result<Page, BrowserError> {
+        if token.is_cancelled() {
+            return Err(BrowserError::Cancelled);
+        }
+        let page = cancellable(token, self.new_page()).await?;
+        if let Err(e) = cancellable(token, self.session().open_on_page(url, &page)).await {
+            let _ = page.close().await;
+            return Err(e);
+        }
+
+        Ok(page)
+    }
+}
+
+impl BrowserPool {
+    pub async fn checkout_page_cancellable(&self, token: &CancellationToken) -> Result<PooledPage, BrowserError> {
+        cancellable(token, self.checkout_page()).await
+    }
+
+    pub async fn with_page_cancellable<T, F, Fut>(
+        &self,
+        url: &str,
+        token: &CancellationToken,
+        callback: F
+    ) -> Result<T, BrowserError>
+    where
+        T: Send + 'static,
+        F: FnOnce(Page) -> Fut,
+        Fut: Future<Output = Result<T, BrowserError>> + Send + 'static
+    {
+        let page = cancellable(token, self.checkout_page_for(url)).await?;
+        let result = cancellable(token, async {
+            page.goto(url).await?;
+            isolate(callback((*page).clone())).await
+        }).await;
+        if matches!(result, Err(BrowserError::Cancelled | BrowserError::CallbackPanicked(_))) {
+            page.discard().await;
+        }
+        result
+    }
+}
@@ -0,0 +1,101 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use chromiumoxide::Page;
+
+use crate::error::BrowserError;
+use super::bindings::inject_script;
+
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum TimeSpoof {
+    Fixed(f64),
+    Offset(f64),
+}
+
+impl TimeSpoof {
+    pub fn fixed(time: SystemTime) -> Self {
+        Self::Fixed(
+            time.duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as f64)
+                .unwrap_or_default()
+        )
+    }
+
+    fn script(&self) -> String {
+        let (fixed, delta) = match self {
+            Self::Fixed(at) => (at.to_string(), 0.0),
+            Self::Offset(offset) => ("null".to_owned(), *offset)
+        };
+        SPOOF_TIME_JS
+            .replace("__FIXED__", &fixed)
+            .replace("__DELTA__", &delta.to_string())
+    }
+}
+
+const SPOOF_TIME_JS: &str = r##"
+(() => {
+    const key = Symbol.for('browser_bridge.time');
+    if (!window[key]) {
+        Object.defineProperty(window, key, {
+            value: {
+                Date: window.Date,
+                timeOrigin: performance.timeOrigin,
+                now: Performance.prototype.now
+            }
+        });
+    }
+    const real = window[key];
+    const RealDate = real.Date;
+    const fixed = __FIXED__;
+    const delta = __DELTA__;
+    const now = fixed === null ? () => RealDate.now() + delta : () => fixed;
+    function Date(...args) {
+        if (!new.target) return new RealDate(now()).toString();
+        return args.length ? new RealDate(...args) : new RealDate(now());
+    }
+    Date.prototype = RealDate.prototype;
+    Date.prototype.constructor = Date;
+    Date.now = now;
+    Date.parse = RealDate.parse;
+    Date.UTC = RealDate.UTC;
+    Date.toString = () => 'function Date() { [native code] }';
+    window.Date = Date;
+    const elapsed = real.now.call(performance);
+    const timeOrigin = fixed === null ? real.timeOrigin + delta : fixed - elapsed;
+    Object.defineProperty(Performance.prototype, 'timeOrigin', {
+        get: () => timeOrigin,
+        configurable: true
+    });
+    Performance.prototype.now = fixed === null
+        ? real.now
+        : function now() { return elapsed; };
+})()
+"##;
+
+pub async fn spoof_time(page: &Page, spoof: TimeSpoof) -> Result<(), BrowserError> {
+    inject_script(page, spoof.script()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_mode_freezes_date_and_performance_now() {
+        let script = TimeSpoof::Fixed(1700000000000.0).script();
+        assert!(script.contains("const fixed = 1700000000000;"));
+        assert!(script.contains("const delta = 0;"));
+        assert!(script.contains("Performance.prototype.now = fixed === null"));
+        assert!(script.contains("fixed - elapsed"));
+        assert!(!script.contains("__"));
+    }
+
+    #[test]
+    fn offset_mode_shifts_date_and_keeps_performance_now_real() {
+        let script = TimeSpoof::Offset(-3600000.0).script();
+        assert!(script.contains("const fixed = null;"));
+        assert!(script.contains("const delta = -3600000;"));
+        assert!(script.contains("? real.now"));
+        assert!(script.contains("real.timeOrigin + delta"));
+    }
+}
@@ -0,0 +1,167 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+        Mutex
+    }
+};
+use serde::{Deserialize, Serialize};
+use chromiumoxide::{
+    cdp::browser_protocol::network::ResourceType,
+    Page
+};
+
+use crate::error::BrowserError;
+use super::browser::BrowserSession;
+use super::extract::extract_all_text;
+use super::identity::unix_now;
+use super::intercept::{InterceptConfig, Interceptor};
+use super::network::MainResponse;
+use super::sink::idempotency_key;
+
+
+pub fn normalize_text(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn content_hash(text: &str) -> String {
+    idempotency_key(&[&normalize_text(text)])
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ContentRecord {
+    pub hash: Option<String>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub checked_at: u64,
+    pub changed_at: u64,
+}
+
+#[derive(Default)]
+pub struct ContentStore {
+    path: Option<PathBuf>,
+    records: Mutex<HashMap<String, ContentRecord>>,
+}
+
+impl ContentStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let records = match fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e)
+        };
+
+        Ok (
+            Self {
+                path: Some(path),
+                records: Mutex::new(records)
+            }
+        )
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let data = serde_json::to_vec(&*self.records.lock().unwrap())
+            .map_err(io::Error::other)?;
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, data)?;
+        fs::rename(&tmp, path)
+    }
+
+    pub fn record(&self, url: &str) -> Option<ContentRecord> {
+        self.records.lock().unwrap().get(url).cloned()
+    }
+
+    pub fn has_changed_text(&self, url: &str, text: &str) -> bool {
+        let hash = content_hash(text);
+        let now = unix_now();
+        let mut records = self.records.lock().unwrap();
+        let record = records.entry(url.to_owned()).or_default();
+        record.checked_at = now;
+        if record.hash.as_deref() == Some(hash.as_str()) {
+            return false;
+        }
+        record.hash = Some(hash);
+        record.changed_at = now;
+        true
+    }
+
+    pub async fn has_changed(&self, url: &str, page: &Page) -> Result<bool, BrowserError> {
+        let text = extract_all_text(page).await?;
+        Ok(self.has_changed_text(url, &text))
+    }
+
+    pub fn record_response(&self, url: &str, response: &MainResponse) {
+        let mut records = self.records.lock().unwrap();
+        let record = records.entry(url.to_owned()).or_default();
+        if let Some(etag) = response.header("etag") {
+            record.etag = Some(etag.to_owned());
+        }
+        if let Some(last_modified) = response.header("last-modified") {
+            record.last_modified = Some(last_modified.to_owned());
+        }
+    }
+
+    pub fn conditional_headers(&self, url: &str) -> Vec<(String, String)> {
+        let Some(record) = self.record(url) else {
+            return Vec::new();
+        };
+        let mut headers = Vec::new();
+        if let Some(etag) = record.etag {
+            headers.push(("If-None-Match".into(), etag));
+        }
+        if let Some(last_modified) = record.last_modified {
+            headers.push(("If-Modified-Since".into(), last_modified));
+        }
+        headers
+    }
+}
+
+impl BrowserSession {
+    pub async fn open_if_changed(&self, url: &str, store: &ContentStore) -> Result<Option<(Page, MainResponse)>, BrowserError> {
+        let page = self.new_page().await?;
+        let headers = store.conditional_headers(url);
+        let interceptor = match headers.is_empty() {
+            true => None,
+            false => {
+                let applied = AtomicBool::new(false);
+                let config = InterceptConfig {
+                    request_hook: Some(Arc::new(move |request| {
+                        if request.resource_type != ResourceType::Document
+                            || applied.swap(true, Ordering::SeqCst) {
+                            return;
+                        }
+                        for (name, value) in &headers {
+                            request.set_header(name, value.clone());
+                        }
+                    })),
+                    ..Default::default()
+                };
+                Some(Interceptor::start(&page, config).await?)
+            }
+        };
+        let opened = self.open_page_for_response(url, page).await;
+        drop(interceptor);
+        let (page, response) = opened?;
+        store.record_response(url, &response);
+        if response.status == 304 || !store.has_changed(url, &page).await? {
+            let _ = page.close().await;
+            return Ok(None);
+        }
+        Ok(Some((page, response)))
+    }
+}
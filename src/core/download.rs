@@ -0,0 +1,240 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use tokio::time::timeout;
+use chromiumoxide::{
+    cdp::browser_protocol::{
+        browser::{
+            BrowserContextId,
+            SetDownloadBehaviorBehavior,
+            SetDownloadBehaviorParams
+        },
+        fetch::{
+            ContinueRequestParams,
+            EventRequestPaused,
+            FailRequestParams,
+            GetResponseBodyParams,
+            HeaderEntry
+        },
+        network::{ErrorReason, ResourceType}
+    },
+    Page
+};
+
+use crate::error::BrowserError;
+use super::browser::BrowserSession;
+use super::context::percent_decode;
+use super::intercept::subscribe_responses;
+
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DownloadedFile {
+    pub url: String,
+    pub mime_type: String,
+    pub file_name: Option<String>,
+    pub bytes: Vec<u8>,
+}
+
+impl DownloadedFile {
+    pub fn save(&self, dir: impl AsRef<Path>) -> std::io::Result<PathBuf> {
+        let name = self.file_name.as_deref()
+            .and_then(safe_file_name)
+            .or_else(|| self.url
+                .split(['?', '#'])
+                .next()
+                .and_then(|u| u.rsplit('/').next())
+                .and_then(safe_file_name)
+            )
+            .unwrap_or_else(|| "download".into());
+        let path = dir.as_ref().join(name);
+        std::fs::write(&path, &self.bytes)?;
+        Ok(path)
+    }
+}
+
+pub enum Navigated {
+    Page(Page),
+    File(DownloadedFile),
+}
+
+fn header<'a>(headers: &'a [HeaderEntry], name: &str) -> Option<&'a str> {
+    headers.iter()
+        .find(|h| h.name.eq_ignore_ascii_case(name))
+        .map(|h| h.value.as_str())
+}
+
+fn is_renderable(mime_type: &str) -> bool {
+    mime_type.is_empty()
+        || mime_type.starts_with("text/")
+        || mime_type.contains("html")
+        || mime_type.contains("xml")
+        || mime_type.contains("json")
+        || mime_type.contains("javascript")
+}
+
+fn safe_file_name(name: &str) -> Option<String> {
+    let name = name.trim();
+    if name.starts_with(['/', '\\']) || Path::new(name).is_absolute() || name.contains('\0') {
+        return None;
+    }
+    let name = name.rsplit(['/', '\\']).next()?;
+    match Path::new(name).file_name().and_then(|n| n.to_str()) {
+        Some(n) if n == name && !n.is_empty() => Some(n.to_owned()),
+        _ => None
+    }
+}
+
+fn attachment_name(disposition: &str) -> Option<String> {
+    let params: Vec<(String, &str)> = disposition.split(';')
+        .skip(1)
+        .filter_map(|p| p.split_once('='))
+        .map(|(key, value)| (key.trim().to_ascii_lowercase(), value.trim()))
+        .collect();
+    // RFC 5987 extended value: charset'language'percent-encoded
+    let extended = params.iter()
+        .find(|(key, _)| key == "filename*")
+        .and_then(|(_, value)| value.splitn(3, '\'').nth(2))
+        .map(percent_decode);
+    let plain = || params.iter()
+        .find(|(key, _)| key == "filename")
+        .map(|(_, value)| value.trim_matches('"').to_owned());
+    safe_file_name(&extended.or_else(plain)?)
+}
+
+async fn capture_file(page: &Page, event: &EventRequestPaused) -> Result<Option<DownloadedFile>, BrowserError> {
+    let headers = event.response_headers.clone().unwrap_or_default();
+    let mime_type = header(&headers, "content-type")
+        .and_then(|v| v.split(';').next())
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+    let disposition = header(&headers, "content-disposition")
+        .unwrap_or_default();
+    let redirect = event.response_status_code
+        .map(|s| (300..400).contains(&s))
+        .unwrap_or(false);
+    if redirect || (is_renderable(&mime_type) && !disposition.contains("attachment")) {
+        page.execute(ContinueRequestParams::new(event.request_id.clone())).await?;
+        return Ok(None);
+    }
+    let body = page.execute(GetResponseBodyParams::new(event.request_id.clone())).await?.result;
+    let bytes = match body.base64_encoded {
+        true => STANDARD.decode(&body.body).map_err(|_| BrowserError::Decoding)?,
+        false => body.body.into_bytes()
+    };
+    page.execute(FailRequestParams::new(event.request_id.clone(), ErrorReason::Aborted)).await?;
+
+    Ok (
+        Some(DownloadedFile {
+            url: event.request.url.clone(),
+            mime_type,
+            file_name: attachment_name(disposition),
+            bytes
+        })
+    )
+}
+
+impl BrowserSession {
+    pub async fn set_download_dir(
+        &self, dir: impl AsRef<Path>, context_id: Option<BrowserContextId>
+    ) -> Result<(), BrowserError> {
+        let _ = std::fs::create_dir_all(dir.as_ref());
+        let dir = std::path::absolute(dir.as_ref())
+            .unwrap_or_else(|_| dir.as_ref().to_path_buf());
+        let mut params = SetDownloadBehaviorParams::new(SetDownloadBehaviorBehavior::Allow);
+        params.browser_context_id = context_id;
+        params.download_path = Some(dir.to_string_lossy().into_owned());
+        params.events_enabled = Some(true);
+        self.browser.execute(params).await?;
+        Ok(())
+    }
+
+    pub async fn open_any(&self, url: &str) -> Result<Navigated, BrowserError> {
+        let page = self.new_page().await?;
+        let main_frame = page.mainframe().await?;
+        let (registration, mut paused) = subscribe_responses(&page, ResourceType::Document).await?;
+        let detect = async {
+            while let Some(event) = paused.recv().await {
+                if Some(&event.frame_id) != main_frame.as_ref() {
+                    let _ = page.execute(ContinueRequestParams::new(event.request_id.clone())).await;
+                    continue;
+                }
+                match capture_file(&page, &event).await {
+                    Ok(None) if event.response_status_code
+                        .map(|s| (300..400).contains(&s))
+                        .unwrap_or(false) => continue,
+                    result => return result
+                }
+            }
+            Ok(None)
+        };
+        let (file, _) = tokio::join!(
+            timeout(
+                Duration::from_millis(self.timings.page_goto_timeout),
+                detect
+            ),
+            self.open_on_page(url, &page)
+        );
+        drop(registration);
+        paused.close();
+        while let Ok(event) = paused.try_recv() {
+            let _ = page.execute(ContinueRequestParams::new(event.request_id.clone())).await;
+        }
+
+        match file.unwrap_or(Ok(None)) {
+            Ok(Some(file)) => {
+                let _ = page.close().await;
+                Ok(Navigated::File(file))
+            },
+            Ok(None) => Ok(Navigated::Page(page)),
+            Err(e) => {
+                let _ = page.close().await;
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_plain_and_extended_filenames() {
+        assert_eq!(attachment_name("attachment; filename=\"report.pdf\"").as_deref(), Some("report.pdf"));
+        assert_eq!(attachment_name("attachment; FileName=data.csv").as_deref(), Some("data.csv"));
+        assert_eq!(
+            attachment_name("attachment; filename=\"fallback.txt\"; filename*=UTF-8''na%C3%AFve%20file.txt").as_deref(),
+            Some("naïve file.txt")
+        );
+        assert_eq!(attachment_name("inline"), None);
+    }
+
+    #[test]
+    fn strips_directories_from_server_names() {
+        assert_eq!(attachment_name("attachment; filename=\"../../.bashrc\"").as_deref(), Some(".bashrc"));
+        assert_eq!(attachment_name("attachment; filename*=UTF-8''..%2F..%2Fevil.sh").as_deref(), Some("evil.sh"));
+        assert_eq!(safe_file_name("..\\..\\win.ini").as_deref(), Some("win.ini"));
+        for name in ["", ".", "..", "dir/..", "/etc/passwd", "\\share\\x", "a\0b"] {
+            assert_eq!(safe_file_name(name), None, "{name:?}");
+        }
+    }
+
+    #[test]
+    fn saves_inside_the_target_dir() {
+        let dir = std::env::temp_dir().join(format!("bb-download-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = DownloadedFile {
+            url: "https://example.com/files/..".into(),
+            mime_type: "application/octet-stream".into(),
+            file_name: Some("/etc/passwd".into()),
+            bytes: b"x".to_vec()
+        };
+        let path = file.save(&dir).unwrap();
+        assert_eq!(path, dir.join("download"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,135 @@
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use chromiumoxide::Page;
+
+use crate::error::BrowserError;
+use super::intercept::NetworkResponse;
+
+
+pub fn charset_from_content_type(content_type: &str) -> Option<&str> {
+    content_type.split(';')
+        .skip(1)
+        .filter_map(|param| param.split_once('='))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case("charset"))
+        .map(|(_, value)| value.trim().trim_matches(['"', '\'']))
+}
+
+fn charset_from_meta(bytes: &[u8]) -> Option<String> {
+    let head = &bytes[..bytes.len().min(1024)];
+    let head = String::from_utf8_lossy(head).to_ascii_lowercase();
+    let mut rest = head.as_str();
+    while let Some(idx) = rest.find("<meta") {
+        rest = &rest[idx + 5..];
+        let tag = &rest[..rest.find('>').unwrap_or(rest.len())];
+        let Some(idx) = tag.find("charset") else {
+            continue;
+        };
+        let Some(value) = tag[idx + 7..].trim_start().strip_prefix('=') else {
+            continue;
+        };
+        let value = value.trim_start().trim_start_matches(['"', '\'']);
+        let end = value.find(|c: char| c == '"' || c == '\'' || c == ';' || c == '/' || c.is_whitespace())
+            .unwrap_or(value.len());
+        if end > 0 {
+            return Some(value[..end].to_owned());
+        }
+    }
+    None
+}
+
+pub fn sniff_encoding(bytes: &[u8], content_type: Option<&str>) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    let declared = content_type
+        .and_then(charset_from_content_type)
+        .and_then(|label| Encoding::for_label(label.as_bytes()));
+    if let Some(encoding) = declared {
+        return encoding;
+    }
+    // a meta tag can't be read in UTF-16, so such a declaration means UTF-8
+    let meta = charset_from_meta(bytes)
+        .and_then(|label| Encoding::for_label(label.as_bytes()))
+        .map(|encoding| if encoding == UTF_16LE || encoding == UTF_16BE { UTF_8 } else { encoding });
+    if let Some(encoding) = meta {
+        return encoding;
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(_) => UTF_8,
+        Err(_) => WINDOWS_1252
+    }
+}
+
+pub fn decode_bytes(bytes: &[u8], content_type: Option<&str>) -> (String, &'static str) {
+    let encoding = sniff_encoding(bytes, content_type);
+    let (text, used, _) = encoding.decode(bytes);
+    (text.into_owned(), used.name())
+}
+
+impl NetworkResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn text(&self) -> Option<String> {
+        let body = self.body.as_ref()?;
+        if !self.raw_body {
+            return Some(String::from_utf8_lossy(body).into_owned());
+        }
+        Some(decode_bytes(body, self.header("content-type")).0)
+    }
+}
+
+pub async fn page_charset(page: &Page) -> Result<String, BrowserError> {
+    page.evaluate("document.characterSet").await?
+        .into_value()
+        .map_err(|_| BrowserError::Serialization)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::{SHIFT_JIS, WINDOWS_1251};
+
+    #[test]
+    fn reads_charset_from_meta_tags() {
+        assert_eq!(
+            charset_from_meta(b"<html><head><META CHARSET=\"Windows-1251\">").as_deref(),
+            Some("windows-1251")
+        );
+        assert_eq!(
+            charset_from_meta(
+                b"<meta http-equiv='Content-Type' content='text/html; charset=Shift_JIS'>"
+            ).as_deref(),
+            Some("shift_jis")
+        );
+        assert_eq!(charset_from_meta(b"<meta charset = utf-8 />").as_deref(), Some("utf-8"));
+        assert_eq!(charset_from_meta(b"<meta name=\"viewport\"><p>charset=koi8-r</p>"), None);
+        assert_eq!(charset_from_meta(b"<meta charset=\"\">"), None);
+    }
+
+    #[test]
+    fn ignores_meta_past_the_prescan_window() {
+        let mut html = vec![b' '; 1100];
+        html.extend_from_slice(b"<meta charset=windows-1251>");
+        assert_eq!(charset_from_meta(&html), None);
+    }
+
+    #[test]
+    fn sniffs_in_precedence_order() {
+        let meta = b"<meta charset=windows-1251>\xcf\xf0\xe8";
+        assert_eq!(sniff_encoding(b"\xef\xbb\xbf<meta charset=shift_jis>", Some("text/html; charset=koi8-r")), UTF_8);
+        assert_eq!(sniff_encoding(meta, Some("text/html; charset=\"Shift_JIS\"")), SHIFT_JIS);
+        assert_eq!(sniff_encoding(meta, Some("text/html; charset=bogus")), WINDOWS_1251);
+        assert_eq!(sniff_encoding(meta, None), WINDOWS_1251);
+        assert_eq!(sniff_encoding("héllo".as_bytes(), None), UTF_8);
+        assert_eq!(sniff_encoding(b"h\xe9llo", None), WINDOWS_1252);
+    }
+
+    #[test]
+    fn treats_utf16_meta_as_utf8() {
+        assert_eq!(sniff_encoding(b"<meta charset=utf-16>", None), UTF_8);
+        assert_eq!(decode_bytes(b"<meta charset=cp1251>\xcf", None), ("<meta charset=cp1251>П".into(), "windows-1251"));
+    }
+}
@@ -33,17 +33,23 @@ pub static PATH: Lazy<String> = Lazy::new(|| {
     extension_path
 });
 
+fn write_if_changed(path: impl AsRef<std::path::Path>, content: &str) -> std::io::Result<()> {
+    match std::fs::read_to_string(path.as_ref()) {
+        Ok(current) if current == content => Ok(()),
+        _ => write_to_file(path, content)
+    }
+}
+
 pub fn init() -> std::io::Result<PathBuf> {
     let current_dir = get_current_dir()?;
     let extension_path = current_dir.join("extension");
-    if extension_path.exists() {
-        return Ok(extension_path);
+    if !extension_path.exists() {
+        create_dir(&extension_path)?;
     }
-    create_dir(&extension_path)?;
     let background_path = extension_path.join("background.js");
     let manifest_path = extension_path.join("manifest.json");
-    write_to_file(background_path, BACKGROUND_JS)?;
-    write_to_file(manifest_path, MANIFEST_JSON)?;
+    write_if_changed(background_path, BACKGROUND_JS)?;
+    write_if_changed(manifest_path, MANIFEST_JSON)?;
     Ok(extension_path)
 }
 
@@ -74,6 +80,7 @@ const BACKGROUND_JS: &'static str = r##"
 const CHROME_URLS = {
     SET_PROXY: 'chrome://set_proxy/',
     RESET_PROXY: 'chrome://reset_proxy',
+    PROXY_BYPASS: 'chrome://proxy_bypass',
     CLEAR_DATA: 'chrome://clear_data',
     CLEAR_COOKIES: 'chrome://clear_cookies',
     INIT_EXTENSION: 'chrome://init_extension',
@@ -84,6 +91,8 @@ const CHROME_URLS = {
 class ProxyManager {
     constructor() {
         this.currentAuthHandler = null;
+        this.currentConfig = null;
+        this.bypassList = ["localhost"];
     }
 
     /**
@@ -153,6 +162,7 @@ class ProxyManager {
 
             // Remove existing auth listeners
             this.removeAuthListener();
+            this.currentConfig = proxyConfig;
 
             // Create new auth handler
             const authHandler = this.createAuthHandler(proxyConfig);
@@ -166,7 +176,7 @@ class ProxyManager {
                         host: proxyConfig.host,
                         port: proxyConfig.port,
                     },
-                    bypassList: ["localhost"]
+                    bypassList: this.bypassList
                 }
             };
 
@@ -246,11 +256,27 @@ class ProxyManager {
         }
     }
 
+    /**
+     * Update hosts that bypass the proxy and re-apply active settings
+     * @param {URL} parsedUrl - Parsed URL object
+     */
+    setBypass(parsedUrl) {
+        const hosts = (parsedUrl.searchParams.get('hosts') || '')
+            .split(',')
+            .map(host => host.trim())
+            .filter(host => host.length > 0);
+        this.bypassList = ["localhost", ...hosts];
+        if (this.currentConfig) {
+            this.setProxy(this.currentConfig);
+        }
+    }
+
     /**
      * Reset proxy to system settings
      */
     resetProxy() {
         this.removeAuthListener();
+        this.currentConfig = null;
         chrome.proxy.settings.set(
             { value: { mode: 'system' }, scope: 'regular' },
             () => {
@@ -333,6 +359,8 @@ class CommandHandler {
                 return proxyConfig && (this.proxyManager.setProxy(proxyConfig), true);
             case url.startsWith(CHROME_URLS.RESET_PROXY):
                 return (this.proxyManager.resetProxy(), true);
+            case url.startsWith(CHROME_URLS.PROXY_BYPASS):
+                return (this.proxyManager.setBypass(parsedUrl), true);
             case url.startsWith(CHROME_URLS.CLEAR_DATA):
                 return (BrowserDataManager.removeBrowsingData(), true);
             case url.startsWith(CHROME_URLS.CLEAR_COOKIES):
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use chromiumoxide::{
+    cdp::{
+        browser_protocol::page::FrameId,
+        js_protocol::runtime::EvaluateParams
+    },
+    Page
+};
+
+use crate::error::BrowserError;
+
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FrameText {
+    pub url: String,
+    pub depth: usize,
+    pub text: String,
+    #[serde(default)]
+    pub lang: Option<String>,
+}
+
+const SAME_ORIGIN_FRAMES_JS: &str = r##"
+(() => {
+    const result = [];
+    const walk = (doc, depth) => {
+        result.push({
+            url: doc.location ? doc.location.href : '',
+            depth,
+            text: doc.body ? doc.body.innerText : ''
+        });
+        for (const frame of doc.querySelectorAll('iframe, frame')) {
+            let child = null;
+            try { child = frame.contentDocument; } catch (e) {}
+            if (child) walk(child, depth + 1);
+        }
+    };
+    walk(document, 0);
+    return result;
+})()
+"##;
+
+async fn frame_inner_text(page: &Page, frame_id: FrameId) -> Result<Option<String>, BrowserError> {
+    let Some(context_id) = page.frame_execution_context(frame_id).await? else {
+        return Ok(None);
+    };
+    let params = EvaluateParams::builder()
+        .expression("document.body ? document.body.innerText : ''")
+        .context_id(context_id)
+        .build()
+        .map_err(|_| BrowserError::ChromeInternal)?;
+    Ok(page.evaluate_expression(params).await?.into_value().ok())
+}
+
+pub async fn extract_frame_texts(page: &Page) -> Result<Vec<FrameText>, BrowserError> {
+    let mut texts: Vec<FrameText> = page.evaluate(SAME_ORIGIN_FRAMES_JS).await?
+        .into_value()
+        .map_err(|_| BrowserError::Serialization)?;
+    let main_frame = page.mainframe().await?;
+    for frame_id in page.frames().await? {
+        if Some(&frame_id) == main_frame.as_ref() {
+            continue;
+        }
+        let Some(url) = page.frame_url(frame_id.clone()).await? else {
+            continue;
+        };
+        if texts.iter().any(|t| t.url == url) {
+            continue;
+        }
+        if let Ok(Some(text)) = frame_inner_text(page, frame_id).await {
+            texts.push(FrameText { url, depth: 1, text, lang: None });
+        }
+    }
+    #[cfg(feature = "lang")]
+    for frame in texts.iter_mut() {
+        frame.lang = super::lang::detect_language(&frame.text).map(|l| l.code().to_owned());
+    }
+    Ok(texts)
+}
+
+pub async fn extract_all_text(page: &Page) -> Result<String, BrowserError> {
+    let texts = extract_frame_texts(page).await?;
+    let mut output = String::new();
+    for (idx, frame) in texts.iter().enumerate() {
+        if idx > 0 {
+            output.push_str(&format!("\n\n--- frame: {} ---\n", frame.url));
+        }
+        output.push_str(frame.text.trim());
+    }
+    Ok(output)
+}
@@ -0,0 +1,415 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use chromiumoxide::Page;
+
+use crate::error::BrowserError;
+use super::bindings::inject_script;
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum DeviceKind {
+    Desktop,
+    Laptop,
+    Mobile,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Platform {
+    #[default]
+    Windows,
+    MacOs,
+    Linux,
+    Android,
+    Ios,
+}
+
+pub(crate) const CHROME_MAJOR: u32 = 135;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatteryProfile {
+    pub charging: bool,
+    pub level: f64,
+    pub charging_time: Option<f64>,
+    pub discharging_time: Option<f64>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionProfile {
+    pub effective_type: String,
+    pub downlink: f64,
+    pub rtt: u64,
+    pub save_data: bool,
+}
+
+static WINDOWS_FONTS: [&str; 16] = [
+    "Arial",
+    "Arial Black",
+    "Calibri",
+    "Cambria",
+    "Comic Sans MS",
+    "Consolas",
+    "Courier New",
+    "Georgia",
+    "Impact",
+    "Lucida Console",
+    "Segoe UI",
+    "Tahoma",
+    "Times New Roman",
+    "Trebuchet MS",
+    "Verdana",
+    "Webdings"
+];
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FontProfile {
+    pub allowed: Vec<String>,
+    pub extra: Vec<String>,
+}
+
+impl FontProfile {
+    pub fn windows() -> Self {
+        Self {
+            allowed: WINDOWS_FONTS.iter()
+                .map(|f| f.to_string())
+                .collect(),
+            extra: Vec::new()
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceProfile {
+    pub voice_uri: String,
+    pub name: String,
+    pub lang: String,
+    pub local_service: bool,
+    #[serde(rename = "default")]
+    pub is_default: bool,
+}
+
+impl VoiceProfile {
+    fn new(name: &str, lang: &str, local_service: bool, is_default: bool) -> Self {
+        Self {
+            voice_uri: name.into(),
+            name: name.into(),
+            lang: lang.into(),
+            local_service,
+            is_default
+        }
+    }
+
+    fn google_voices() -> Vec<Self> {
+        [
+            ("Google Deutsch", "de-DE"),
+            ("Google US English", "en-US"),
+            ("Google UK English Female", "en-GB"),
+            ("Google UK English Male", "en-GB"),
+            ("Google español", "es-ES"),
+            ("Google français", "fr-FR"),
+            ("Google italiano", "it-IT"),
+            ("Google 日本語", "ja-JP"),
+            ("Google русский", "ru-RU"),
+            ("Google 普通话（中国大陆）", "zh-CN")
+        ].into_iter()
+            .map(|(name, lang)| Self::new(name, lang, false, false))
+            .collect()
+    }
+
+    pub fn windows() -> Vec<Self> {
+        let mut voices = vec![
+            Self::new("Microsoft David - English (United States)", "en-US", true, true),
+            Self::new("Microsoft Mark - English (United States)", "en-US", true, false),
+            Self::new("Microsoft Zira - English (United States)", "en-US", true, false)
+        ];
+        voices.extend(Self::google_voices());
+        voices
+    }
+
+    pub fn macos() -> Vec<Self> {
+        let mut voices = vec![
+            Self::new("Samantha", "en-US", true, true),
+            Self::new("Daniel", "en-GB", true, false),
+            Self::new("Karen", "en-AU", true, false),
+            Self::new("Moira", "en-IE", true, false),
+            Self::new("Thomas", "fr-FR", true, false),
+            Self::new("Anna", "de-DE", true, false)
+        ];
+        voices.extend(Self::google_voices());
+        voices
+    }
+
+    pub fn linux() -> Vec<Self> {
+        let mut voices = Self::google_voices();
+        if let Some(voice) = voices.iter_mut().find(|v| v.lang == "en-US") {
+            voice.is_default = true;
+        }
+        voices
+    }
+
+    pub fn ios() -> Vec<Self> {
+        vec![
+            Self::new("Samantha", "en-US", true, true),
+            Self::new("Daniel", "en-GB", true, false),
+            Self::new("Karen", "en-AU", true, false),
+            Self::new("Thomas", "fr-FR", true, false),
+            Self::new("Anna", "de-DE", true, false),
+            Self::new("Mónica", "es-ES", true, false)
+        ]
+    }
+
+    pub fn for_platform(platform: Platform) -> Vec<Self> {
+        match platform {
+            Platform::Windows => Self::windows(),
+            Platform::MacOs => Self::macos(),
+            Platform::Linux => Self::linux(),
+            Platform::Android => Self::android(),
+            Platform::Ios => Self::ios()
+        }
+    }
+
+    pub fn android() -> Vec<Self> {
+        vec![
+            Self::new("English United States", "en-US", true, true),
+            Self::new("English United Kingdom", "en-GB", true, false),
+            Self::new("Deutsch Deutschland", "de-DE", true, false),
+            Self::new("español España", "es-ES", true, false),
+            Self::new("français France", "fr-FR", true, false)
+        ]
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FingerprintProfile {
+    pub device: DeviceKind,
+    #[serde(default)]
+    pub platform: Platform,
+    pub battery: BatteryProfile,
+    pub connection: ConnectionProfile,
+    pub sensors: bool,
+    #[serde(default)]
+    pub fonts: Option<FontProfile>,
+    #[serde(default)]
+    pub voices: Vec<VoiceProfile>,
+}
+
+impl FingerprintProfile {
+    pub fn for_device(device: DeviceKind) -> Self {
+        let mut rng = rand::thread_rng();
+        let battery = match device {
+            DeviceKind::Desktop => BatteryProfile {
+                charging: true,
+                level: 1.0,
+                charging_time: Some(0.0),
+                discharging_time: None
+            },
+            DeviceKind::Laptop | DeviceKind::Mobile => {
+                let charging = rng.gen_bool(0.4);
+                let level = (rng.gen_range(0.25..0.98_f64) * 100.0).round() / 100.0;
+                BatteryProfile {
+                    charging,
+                    level,
+                    charging_time: charging.then(|| rng.gen_range(600.0..5400.0_f64).round()),
+                    discharging_time: (!charging).then(|| rng.gen_range(3600.0..21600.0_f64).round())
+                }
+            }
+        };
+        let connection = match device {
+            DeviceKind::Mobile => ConnectionProfile {
+                effective_type: "4g".into(),
+                downlink: rng.gen_range(15..100) as f64 / 10.0,
+                rtt: rng.gen_range(2..7) * 25,
+                save_data: false
+            },
+            _ => ConnectionProfile {
+                effective_type: "4g".into(),
+                downlink: 10.0,
+                rtt: rng.gen_range(1..4) * 50,
+                save_data: false
+            }
+        };
+        let platform = match device {
+            DeviceKind::Mobile => Platform::Android,
+            _ => Platform::Windows
+        };
+
+        Self {
+            device,
+            platform,
+            battery,
+            connection,
+            sensors: device == DeviceKind::Mobile,
+            fonts: None,
+            voices: VoiceProfile::for_platform(platform)
+        }
+    }
+
+    pub fn for_platform(device: DeviceKind, platform: Platform) -> Self {
+        Self {
+            platform,
+            voices: VoiceProfile::for_platform(platform),
+            ..Self::for_device(device)
+        }
+    }
+
+    pub fn user_agent(&self, chrome_major: u32) -> String {
+        let os = match self.platform {
+            Platform::Windows => "Windows NT 10.0; Win64; x64",
+            Platform::MacOs => "Macintosh; Intel Mac OS X 10_15_7",
+            Platform::Linux => "X11; Linux x86_64",
+            Platform::Android => "Linux; Android 10; K",
+            Platform::Ios => {
+                return format!(
+                    "Mozilla/5.0 (iPhone; CPU iPhone OS 17_5 like Mac OS X) AppleWebKit/605.1.15 \
+                    (KHTML, like Gecko) CriOS/{chrome_major}.0.0.0 Mobile/15E148 Safari/604.1"
+                );
+            }
+        };
+        let mobile = match self.device {
+            DeviceKind::Mobile => "Mobile ",
+            _ => ""
+        };
+        format!(
+            "Mozilla/5.0 ({os}) AppleWebKit/537.36 (KHTML, like Gecko) \
+            Chrome/{chrome_major}.0.0.0 {mobile}Safari/537.36"
+        )
+    }
+
+    pub fn to_script(&self) -> Result<String, BrowserError> {
+        let profile = serde_json::to_string(self)
+            .map_err(|_| BrowserError::Serialization)?;
+        Ok(FINGERPRINT_JS.replace("__PROFILE__", &profile))
+    }
+}
+
+impl Default for FingerprintProfile {
+    fn default() -> Self {
+        Self::for_device(DeviceKind::Desktop)
+    }
+}
+
+const FINGERPRINT_JS: &str = r##"
+(() => {
+    const fp = __PROFILE__;
+    const getter = (obj, prop, value) => Object.defineProperty(obj, prop, {
+        get: () => value, configurable: true
+    });
+    const battery = {
+        charging: fp.battery.charging,
+        level: fp.battery.level,
+        chargingTime: fp.battery.chargingTime ?? Infinity,
+        dischargingTime: fp.battery.dischargingTime ?? Infinity,
+        onchargingchange: null,
+        onchargingtimechange: null,
+        ondischargingtimechange: null,
+        onlevelchange: null,
+        addEventListener() {},
+        removeEventListener() {},
+        dispatchEvent() { return true; }
+    };
+    if (window.BatteryManager) Object.setPrototypeOf(battery, BatteryManager.prototype);
+    const getBattery = function getBattery() { return Promise.resolve(battery); };
+    getBattery.toString = () => 'function getBattery() { [native code] }';
+    Navigator.prototype.getBattery = getBattery;
+    const connection = navigator.connection;
+    if (connection) {
+        getter(connection, 'effectiveType', fp.connection.effectiveType);
+        getter(connection, 'downlink', fp.connection.downlink);
+        getter(connection, 'rtt', fp.connection.rtt);
+        getter(connection, 'saveData', fp.connection.saveData);
+    }
+    if (fp.fonts) {
+        const generic = ['serif', 'sans-serif', 'monospace', 'cursive', 'fantasy', 'system-ui',
+            'ui-serif', 'ui-sans-serif', 'ui-monospace', 'ui-rounded', 'emoji', 'math', 'fangsong',
+            'inherit', 'initial', 'unset', 'revert'];
+        const normalize = f => f.trim().replace(/^['"]|['"]$/g, '').toLowerCase();
+        const allowed = new Set(fp.fonts.allowed.map(normalize));
+        const extra = new Set(fp.fonts.extra.map(normalize));
+        const visible = f => {
+            const name = normalize(f);
+            return generic.includes(name) || extra.has(name) || !allowed.size || allowed.has(name);
+        };
+        const filterFamilies = value => {
+            if (typeof value !== 'string') return value;
+            const kept = value.split(',').filter(visible);
+            return kept.length ? kept.join(',') : 'sans-serif';
+        };
+        const style = CSSStyleDeclaration.prototype;
+        const family = Object.getOwnPropertyDescriptor(style, 'fontFamily');
+        if (family && family.set) {
+            Object.defineProperty(style, 'fontFamily', {
+                get: family.get,
+                set(value) { family.set.call(this, filterFamilies(value)); },
+                configurable: true
+            });
+        }
+        const font = Object.getOwnPropertyDescriptor(style, 'font');
+        if (font && font.set) {
+            Object.defineProperty(style, 'font', {
+                get: font.get,
+                set(value) {
+                    const match = typeof value === 'string' && value.match(/^(.*?\d[\w%.]*(?:\/\S+)?\s+)(.+)$/);
+                    font.set.call(this, match ? match[1] + filterFamilies(match[2]) : value);
+                },
+                configurable: true
+            });
+        }
+        const setProperty = style.setProperty;
+        style.setProperty = function (name, value, priority) {
+            if (String(name).toLowerCase() === 'font-family') value = filterFamilies(value);
+            return setProperty.call(this, name, value, priority);
+        };
+        if (document.fonts && document.fonts.check) {
+            const check = document.fonts.check.bind(document.fonts);
+            document.fonts.check = (spec, text) => {
+                const families = String(spec).replace(/^.*?\d[\w%.]*(?:\/\S+)?\s+/, '').split(',');
+                if (families.some(f => extra.has(normalize(f)))) return true;
+                if (!families.every(visible)) return false;
+                return check(spec, text);
+            };
+        }
+        if (window.queryLocalFonts) {
+            const query = window.queryLocalFonts.bind(window);
+            window.queryLocalFonts = (...args) => query(...args)
+                .then(fonts => fonts.filter(f => visible(f.family)));
+        }
+    }
+    if (fp.voices.length && window.speechSynthesis) {
+        const voices = fp.voices.map(v => {
+            const voice = {
+                voiceURI: v.voiceUri,
+                name: v.name,
+                lang: v.lang,
+                localService: v.localService,
+                default: v.default
+            };
+            if (window.SpeechSynthesisVoice) Object.setPrototypeOf(voice, SpeechSynthesisVoice.prototype);
+            return voice;
+        });
+        const getVoices = function getVoices() { return voices.slice(); };
+        getVoices.toString = () => 'function getVoices() { [native code] }';
+        SpeechSynthesis.prototype.getVoices = getVoices;
+        setTimeout(() => speechSynthesis.dispatchEvent(new Event('voiceschanged')), 50);
+    }
+    if (fp.sensors && window.DeviceOrientationEvent) {
+        let alpha = Math.random() * 360;
+        const beta = 20 + Math.random() * 40;
+        const gamma = -5 + Math.random() * 10;
+        const jitter = () => (Math.random() - 0.5) * 0.6;
+        setInterval(() => {
+            alpha = (alpha + jitter() + 360) % 360;
+            window.dispatchEvent(new DeviceOrientationEvent('deviceorientation', {
+                alpha, beta: beta + jitter(), gamma: gamma + jitter(), absolute: false
+            }));
+        }, 250);
+    }
+})()
+"##;
+
+pub async fn apply_fingerprint(page: &Page, profile: &FingerprintProfile) -> Result<(), BrowserError> {
+    inject_script(page, profile.to_script()?).await
+}
@@ -0,0 +1,325 @@
+use serde::{Deserialize, Serialize};
+use chromiumoxide::cdp::browser_protocol::system_info::GetInfoParams;
+
+use crate::error::BrowserError;
+use super::browser::BrowserSession;
+
+
+static OWNED_PREFIXES: [&str; 18] = [
+    "--lang=",
+    "--force-color-profile=",
+    "--disable-gpu",
+    "--no-sandbox",
+    "--disable-blink-features=",
+    "--disable-default-apps",
+    "--no-first-run",
+    "--disable-sync",
+    "--no-default-browser-check",
+    "--disable-smooth-scrolling",
+    "--enable-features=",
+    "--disable-features=",
+    "--disable-setuid-sandbox",
+    "--use-gl=",
+    "--use-angle=",
+    "--enable-unsafe-swiftshader",
+    "--disable-webgl",
+    "--disable-3d-apis"
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebGlMode {
+    #[default]
+    Gpu,
+    SwiftShader,
+    Disabled,
+}
+
+impl WebGlMode {
+    pub fn to_args(&self) -> Vec<String> {
+        match self {
+            WebGlMode::Gpu => Vec::new(),
+            WebGlMode::SwiftShader => vec![
+                "--use-gl=angle".into(),
+                "--use-angle=swiftshader".into(),
+                "--enable-unsafe-swiftshader".into()
+            ],
+            WebGlMode::Disabled => vec![
+                "--disable-gpu".into(),
+                "--disable-webgl".into(),
+                "--disable-3d-apis".into()
+            ]
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ChromeFlags {
+    pub lang: Option<String>,
+    pub color_profile: Option<String>,
+    pub webgl: WebGlMode,
+    pub sandbox: bool,
+    pub automation_controlled: bool,
+    pub default_apps: bool,
+    pub first_run: bool,
+    pub sync: bool,
+    pub default_browser_check: bool,
+    pub smooth_scrolling: bool,
+    pub enable_features: Vec<String>,
+    pub disable_features: Vec<String>,
+}
+
+impl Default for ChromeFlags {
+    fn default() -> Self {
+        Self {
+            lang: Some("en_US".into()),
+            color_profile: None,
+            webgl: WebGlMode::Gpu,
+            sandbox: false,
+            automation_controlled: false,
+            default_apps: false,
+            first_run: false,
+            sync: false,
+            default_browser_check: false,
+            smooth_scrolling: false,
+            enable_features: Vec::new(),
+            disable_features: vec!["TranslateUI".into()]
+        }
+    }
+}
+
+impl ChromeFlags {
+    pub fn enable_feature(&mut self, feature: &str) -> &mut Self {
+        self.disable_features.retain(|f| f != feature);
+        if !self.enable_features.iter().any(|f| f == feature) {
+            self.enable_features.push(feature.into());
+        }
+        self
+    }
+
+    pub fn disable_feature(&mut self, feature: &str) -> &mut Self {
+        self.enable_features.retain(|f| f != feature);
+        if !self.disable_features.iter().any(|f| f == feature) {
+            self.disable_features.push(feature.into());
+        }
+        self
+    }
+
+    pub fn validate(&self, extra_args: &[String]) -> Result<(), BrowserError> {
+        for (name, value) in [("lang", &self.lang), ("color_profile", &self.color_profile)] {
+            if let Some(value) = value {
+                if value.is_empty() || value.contains(char::is_whitespace) {
+                    return Err(BrowserError::InvalidFlags(
+                        format!("{name} `{value}` is empty or contains whitespace")
+                    ));
+                }
+            }
+        }
+        for (list, features) in [
+            ("enable_features", &self.enable_features),
+            ("disable_features", &self.disable_features)
+        ] {
+            if let Some(feature) = features.iter()
+                .find(|f| f.is_empty() || f.contains(|c: char| c == ',' || c == '=' || c.is_whitespace())) {
+                return Err(BrowserError::InvalidFlags(
+                    format!("{list} entry `{feature}` is not a single feature name")
+                ));
+            }
+            if let Some((_, feature)) = features.iter().enumerate()
+                .find(|(i, f)| features[..*i].contains(f)) {
+                return Err(BrowserError::InvalidFlags(
+                    format!("feature `{feature}` is listed twice in {list}")
+                ));
+            }
+        }
+        if let Some(feature) = self.enable_features.iter()
+            .find(|f| self.disable_features.contains(f)) {
+            return Err(BrowserError::InvalidFlags(
+                format!("feature `{feature}` is both enabled and disabled")
+            ));
+        }
+        if let Some(arg) = extra_args.iter()
+            .find(|a| OWNED_PREFIXES.iter().any(|p| a.starts_with(p))) {
+            return Err(BrowserError::InvalidFlags(
+                format!("`{arg}` conflicts with a typed ChromeFlags option")
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if !self.automation_controlled {
+            args.push("--disable-blink-features=AutomationControlled".into());
+        }
+        if !self.default_apps {
+            args.push("--disable-default-apps".into());
+        }
+        if !self.first_run {
+            args.push("--no-first-run".into());
+        }
+        if !self.sync {
+            args.push("--disable-sync".into());
+        }
+        if let Some(lang) = &self.lang {
+            args.push(format!("--lang={lang}"));
+        }
+        if !self.default_browser_check {
+            args.push("--no-default-browser-check".into());
+        }
+        if !self.smooth_scrolling {
+            args.push("--disable-smooth-scrolling".into());
+        }
+        if let Some(color_profile) = &self.color_profile {
+            args.push(format!("--force-color-profile={color_profile}"));
+        }
+        args.extend(self.webgl.to_args());
+        if !self.enable_features.is_empty() {
+            args.push(format!("--enable-features={}", self.enable_features.join(",")));
+        }
+        if !self.disable_features.is_empty() {
+            args.push(format!("--disable-features={}", self.disable_features.join(",")));
+        }
+        args
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GpuStatus {
+    pub webgl: String,
+    pub renderer: String,
+}
+
+impl GpuStatus {
+    pub fn webgl_available(&self) -> bool {
+        !self.webgl.is_empty()
+            && !self.webgl.starts_with("unavailable")
+            && !self.webgl.starts_with("disabled")
+    }
+
+    pub fn is_swiftshader(&self) -> bool {
+        self.renderer.to_lowercase().contains("swiftshader")
+    }
+
+    pub fn matches(&self, mode: WebGlMode) -> bool {
+        match mode {
+            WebGlMode::Gpu => self.webgl_available() && !self.is_swiftshader(),
+            WebGlMode::SwiftShader => self.webgl_available() && self.is_swiftshader(),
+            WebGlMode::Disabled => !self.webgl_available()
+        }
+    }
+}
+
+impl BrowserSession {
+    pub async fn gpu_status(&self) -> Result<GpuStatus, BrowserError> {
+        let gpu = self.browser.execute(GetInfoParams::default()).await?
+            .result
+            .gpu;
+        let webgl = gpu.feature_status.as_ref()
+            .and_then(|s| s.get("webgl"))
+            .and_then(|s| s.as_str())
+            .unwrap_or_default()
+            .to_owned();
+        let renderer = gpu.aux_attributes.as_ref()
+            .and_then(|a| a.get("glRenderer"))
+            .and_then(|r| r.as_str())
+            .map(|r| r.to_owned())
+            .or_else(|| gpu.devices.first().map(|d| d.device_string.clone()))
+            .unwrap_or_default();
+
+        Ok(GpuStatus { webgl, renderer })
+    }
+
+    pub async fn verify_webgl(&self) -> Result<GpuStatus, BrowserError> {
+        let mode = self.effective_config.webgl;
+        let status = self.gpu_status().await?;
+        if !status.matches(mode) {
+            return Err(BrowserError::InvalidFlags(
+                format!(
+                    "webgl mode {mode:?} not in effect (webgl: `{}`, renderer: `{}`)",
+                    status.webgl, status.renderer
+                )
+            ));
+        }
+        Ok(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_flags_render_and_validate() {
+        let flags = ChromeFlags::default();
+        let args = flags.to_args();
+        assert!(args.contains(&"--lang=en_US".to_string()));
+        assert!(args.contains(&"--disable-features=TranslateUI".to_string()));
+        assert!(args.contains(&"--disable-blink-features=AutomationControlled".to_string()));
+        assert!(!args.iter().any(|a| a.starts_with("--enable-features=")));
+        assert!(flags.validate(&[]).is_ok());
+    }
+
+    #[test]
+    fn feature_helpers_keep_lists_disjoint() {
+        let mut flags = ChromeFlags::default();
+        flags.enable_feature("TranslateUI").enable_feature("NetworkService");
+        assert_eq!(flags.enable_features, ["TranslateUI", "NetworkService"]);
+        assert!(flags.disable_features.is_empty());
+        let args = flags.to_args();
+        assert!(args.contains(&"--enable-features=TranslateUI,NetworkService".to_string()));
+        assert!(!args.iter().any(|a| a.starts_with("--disable-features=")));
+        assert!(flags.validate(&[]).is_ok());
+    }
+
+    #[test]
+    fn webgl_mode_renders_its_switches() {
+        let mut flags = ChromeFlags {
+            webgl: WebGlMode::SwiftShader,
+            ..Default::default()
+        };
+        assert!(flags.to_args().contains(&"--use-angle=swiftshader".to_string()));
+        flags.webgl = WebGlMode::Disabled;
+        let args = flags.to_args();
+        assert!(args.contains(&"--disable-webgl".to_string()));
+        assert!(!args.iter().any(|a| a.starts_with("--use-angle=")));
+    }
+
+    #[test]
+    fn rejects_inconsistent_typed_fields() {
+        let mut flags = ChromeFlags::default();
+        flags.enable_features.push("TranslateUI".into());
+        assert!(matches!(flags.validate(&[]), Err(BrowserError::InvalidFlags(_))));
+
+        let flags = ChromeFlags {
+            enable_features: vec!["A,B".into()],
+            ..Default::default()
+        };
+        assert!(flags.validate(&[]).is_err());
+
+        let mut flags = ChromeFlags::default();
+        flags.disable_features.push("TranslateUI".into());
+        assert!(flags.validate(&[]).is_err());
+
+        let flags = ChromeFlags {
+            lang: Some(String::new()),
+            ..Default::default()
+        };
+        assert!(flags.validate(&[]).is_err());
+
+        let flags = ChromeFlags {
+            color_profile: Some("display p3".into()),
+            ..Default::default()
+        };
+        assert!(flags.validate(&[]).is_err());
+    }
+
+    #[test]
+    fn rejects_raw_args_owned_by_typed_fields() {
+        let flags = ChromeFlags::default();
+        assert!(flags.validate(&["--lang=de".into()]).is_err());
+        assert!(flags.validate(&["--disable-gpu".into()]).is_err());
+        assert!(flags.validate(&["--window-size=1280,720".into()]).is_ok());
+    }
+}
@@ -0,0 +1,202 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    future::Future,
+    pin::Pin,
+    sync::Mutex,
+    time::{Duration, Instant}
+};
+use tokio::time::sleep;
+
+use crate::error::BrowserError;
+
+
+pub type FrontierFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, BrowserError>> + Send + 'a>>;
+
+pub trait Frontier: Send + Sync {
+    fn push<'a>(&'a self, url: &'a str) -> FrontierFuture<'a, bool>;
+    fn push_keyed<'a>(&'a self, key: &'a str, url: &'a str) -> FrontierFuture<'a, bool>;
+    fn pop(&self) -> FrontierFuture<'_, Option<String>>;
+    fn ack<'a>(&'a self, url: &'a str) -> FrontierFuture<'a, ()>;
+    fn pending(&self) -> FrontierFuture<'_, usize>;
+    fn is_visited<'a>(&'a self, url: &'a str) -> FrontierFuture<'a, bool>;
+}
+
+pub trait RateLimiter: Send + Sync {
+    fn acquire<'a>(&'a self, domain: &'a str) -> FrontierFuture<'a, Duration>;
+    fn penalize<'a>(&'a self, domain: &'a str, wait: Duration) -> FrontierFuture<'a, ()>;
+}
+
+pub fn url_host(url: &str) -> Option<&str> {
+    let rest = url.split_once("://")?.1;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit_once('@').map(|(_, h)| h).unwrap_or(authority);
+    // bracketed IPv6 literals keep their colons, e.g. [::1]:8080
+    let host = match host.starts_with('[') {
+        true => &host[..=host.find(']')?],
+        false => host.split(':').next()?
+    };
+    (!host.is_empty()).then_some(host)
+}
+
+pub async fn wait_turn(limiter: &dyn RateLimiter, url: &str) -> Result<(), BrowserError> {
+    let Some(host) = url_host(url) else {
+        return Ok(());
+    };
+    loop {
+        let wait = limiter.acquire(host).await?;
+        if wait.is_zero() {
+            return Ok(());
+        }
+        sleep(wait).await;
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryFrontier {
+    queue: Mutex<VecDeque<String>>,
+    visited: Mutex<HashSet<String>>,
+    keys: Mutex<HashSet<String>>,
+}
+
+impl MemoryFrontier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Frontier for MemoryFrontier {
+    fn push<'a>(&'a self, url: &'a str) -> FrontierFuture<'a, bool> {
+        Box::pin(async move {
+            if !self.visited.lock().unwrap().insert(url.to_owned()) {
+                return Ok(false);
+            }
+            self.queue.lock().unwrap().push_back(url.to_owned());
+            Ok(true)
+        })
+    }
+
+    fn push_keyed<'a>(&'a self, key: &'a str, url: &'a str) -> FrontierFuture<'a, bool> {
+        Box::pin(async move {
+            if !self.keys.lock().unwrap().insert(key.to_owned()) {
+                return Ok(false);
+            }
+            self.visited.lock().unwrap().insert(url.to_owned());
+            self.queue.lock().unwrap().push_back(url.to_owned());
+            Ok(true)
+        })
+    }
+
+    fn pop(&self) -> FrontierFuture<'_, Option<String>> {
+        Box::pin(async move {
+            Ok(self.queue.lock().unwrap().pop_front())
+        })
+    }
+
+    fn ack<'a>(&'a self, _url: &'a str) -> FrontierFuture<'a, ()> {
+        Box::pin(async move {
+            Ok(())
+        })
+    }
+
+    fn pending(&self) -> FrontierFuture<'_, usize> {
+        Box::pin(async move {
+            Ok(self.queue.lock().unwrap().len())
+        })
+    }
+
+    fn is_visited<'a>(&'a self, url: &'a str) -> FrontierFuture<'a, bool> {
+        Box::pin(async move {
+            Ok(self.visited.lock().unwrap().contains(url))
+        })
+    }
+}
+
+pub struct MemoryRateLimiter {
+    interval: Duration,
+    next_slot: Mutex<HashMap<String, Instant>>,
+}
+
+impl MemoryRateLimiter {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_slot: Mutex::new(HashMap::new())
+        }
+    }
+}
+
+impl RateLimiter for MemoryRateLimiter {
+    fn acquire<'a>(&'a self, domain: &'a str) -> FrontierFuture<'a, Duration> {
+        Box::pin(async move {
+            let now = Instant::now();
+            let mut slots = self.next_slot.lock().unwrap();
+            match slots.get(domain) {
+                Some(next) if *next > now => Ok(*next - now),
+                _ => {
+                    slots.insert(domain.to_owned(), now + self.interval);
+                    Ok(Duration::ZERO)
+                }
+            }
+        })
+    }
+
+    fn penalize<'a>(&'a self, domain: &'a str, wait: Duration) -> FrontierFuture<'a, ()> {
+        Box::pin(async move {
+            let until = Instant::now() + wait;
+            let mut slots = self.next_slot.lock().unwrap();
+            let slot = slots.entry(domain.to_owned()).or_insert(until);
+            *slot = (*slot).max(until);
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_hosts_from_urls() {
+        assert_eq!(url_host("https://example.com/path?q=1"), Some("example.com"));
+        assert_eq!(url_host("http://user:p@ss@example.com:8080#top"), Some("example.com"));
+        assert_eq!(url_host("http://[::1]:8080/"), Some("[::1]"));
+        assert_eq!(url_host("http://[2001:db8::1]/"), Some("[2001:db8::1]"));
+        assert_eq!(url_host("http://[::1/"), None);
+        assert_eq!(url_host("example.com"), None);
+        assert_eq!(url_host("file:///tmp/x"), None);
+    }
+
+    #[tokio::test]
+    async fn memory_frontier_dedups_urls_and_keys() {
+        let frontier = MemoryFrontier::new();
+        assert!(frontier.push("https://a.test/1").await.unwrap());
+        assert!(!frontier.push("https://a.test/1").await.unwrap());
+        assert!(frontier.push_keyed("item-7", "https://a.test/7?ref=x").await.unwrap());
+        assert!(!frontier.push_keyed("item-7", "https://a.test/7?ref=y").await.unwrap());
+        assert!(frontier.is_visited("https://a.test/7?ref=x").await.unwrap());
+        assert!(!frontier.is_visited("https://a.test/7?ref=y").await.unwrap());
+        assert_eq!(frontier.pending().await.unwrap(), 2);
+        assert_eq!(frontier.pop().await.unwrap().as_deref(), Some("https://a.test/1"));
+        frontier.ack("https://a.test/1").await.unwrap();
+        assert_eq!(frontier.pop().await.unwrap().as_deref(), Some("https://a.test/7?ref=x"));
+        assert_eq!(frontier.pop().await.unwrap(), None);
+        assert!(!frontier.push("https://a.test/1").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn memory_rate_limiter_spaces_and_penalizes_hosts() {
+        let limiter = MemoryRateLimiter::new(Duration::from_secs(10));
+        assert!(limiter.acquire("a.test").await.unwrap().is_zero());
+        let wait = limiter.acquire("a.test").await.unwrap();
+        assert!(wait > Duration::from_secs(9) && wait <= Duration::from_secs(10));
+        assert!(limiter.acquire("b.test").await.unwrap().is_zero());
+
+        limiter.penalize("a.test", Duration::from_secs(60)).await.unwrap();
+        assert!(limiter.acquire("a.test").await.unwrap() > Duration::from_secs(59));
+        limiter.penalize("a.test", Duration::from_secs(1)).await.unwrap();
+        assert!(limiter.acquire("a.test").await.unwrap() > Duration::from_secs(59));
+
+        limiter.penalize("c.test", Duration::from_secs(5)).await.unwrap();
+        assert!(limiter.acquire("c.test").await.unwrap() > Duration::from_secs(4));
+    }
+}
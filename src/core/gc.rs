@@ -0,0 +1,203 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+        Mutex
+    },
+    time::{Duration, Instant}
+};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    task::JoinHandle,
+    time::sleep
+};
+use chromiumoxide::{
+    cdp::browser_protocol::target::{CloseTargetParams, GetTargetsParams, TargetId},
+    Page
+};
+
+use crate::error::BrowserError;
+use super::browser::BrowserSession;
+
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TabGcOptions {
+    pub max_age: u64,
+    pub interval: u64,
+    pub url_prefixes: Vec<String>,
+}
+
+impl Default for TabGcOptions {
+    fn default() -> Self {
+        Self {
+            max_age: 60000,
+            interval: 30000,
+            url_prefixes: vec!["about:blank".into(), "chrome://".into()]
+        }
+    }
+}
+
+type OwnedTargets = Arc<Mutex<HashMap<TargetId, Option<Instant>>>>;
+
+fn track(owned: &OwnedTargets, page: &Page) {
+    owned.lock().unwrap().insert(page.target_id().clone(), None);
+}
+
+fn release(owned: &OwnedTargets, page: &Page) {
+    owned.lock().unwrap().insert(page.target_id().clone(), Some(Instant::now()));
+}
+
+pub struct TabGc {
+    options: TabGcOptions,
+    owned: OwnedTargets,
+}
+
+impl TabGc {
+    pub fn new(options: TabGcOptions) -> Self {
+        Self {
+            options,
+            owned: Arc::new(Mutex::new(HashMap::new()))
+        }
+    }
+
+    pub fn options(&self) -> &TabGcOptions {
+        &self.options
+    }
+
+    pub fn track(&self, page: &Page) {
+        track(&self.owned, page);
+    }
+
+    pub fn release(&self, page: &Page) {
+        release(&self.owned, page);
+    }
+
+    fn is_zombie_url(&self, url: &str) -> bool {
+        self.options.url_prefixes.iter().any(|p| url.starts_with(p.as_str()))
+    }
+
+    // untracked targets age from when the gc first saw them, released ones
+    // from their release, and live ones are never candidates
+    fn select(&self, pages: Vec<(TargetId, String)>, keep: &[TargetId], now: Instant) -> Vec<TargetId> {
+        let max_age = Duration::from_millis(self.options.max_age);
+        let mut owned = self.owned.lock().unwrap();
+        owned.retain(|target_id, _| pages.iter().any(|(id, _)| id == target_id));
+        pages.into_iter()
+            .filter(|(_, url)| self.is_zombie_url(url))
+            .filter(|(target_id, _)| !keep.contains(target_id))
+            .filter(|(target_id, _)| owned.entry(target_id.clone())
+                .or_insert(Some(now))
+                .is_some_and(|since| now.saturating_duration_since(since) >= max_age)
+            )
+            .map(|(target_id, _)| target_id)
+            .collect()
+    }
+
+    pub async fn collect(&self, session: &BrowserSession, keep: &[TargetId]) -> Result<Vec<TargetId>, BrowserError> {
+        let pages = session.browser.execute(GetTargetsParams::default()).await?
+            .result.target_infos
+            .into_iter()
+            .filter(|t| t.r#type == "page")
+            .map(|t| (t.target_id, t.url))
+            .collect();
+        let candidates = self.select(pages, keep, Instant::now());
+        let mut closed = Vec::new();
+        for target_id in candidates {
+            if session.browser.execute(CloseTargetParams::new(target_id.clone())).await.is_ok() {
+                self.owned.lock().unwrap().remove(&target_id);
+                closed.push(target_id);
+            }
+        }
+        Ok(closed)
+    }
+
+    pub fn spawn(self, session: Arc<BrowserSession>) -> TabGcHandle {
+        let collected = Arc::new(AtomicUsize::new(0));
+        let owned = self.owned.clone();
+        let task_collected = collected.clone();
+        let handle = tokio::task::spawn(async move {
+            loop {
+                sleep(
+                    Duration::from_millis(self.options.interval)
+                ).await;
+                if let Ok(closed) = self.collect(&session, &[]).await {
+                    task_collected.fetch_add(closed.len(), Ordering::Relaxed);
+                }
+            }
+        });
+
+        TabGcHandle {
+            handle,
+            owned,
+            collected
+        }
+    }
+}
+
+pub struct TabGcHandle {
+    handle: JoinHandle<()>,
+    owned: OwnedTargets,
+    collected: Arc<AtomicUsize>,
+}
+
+impl TabGcHandle {
+    pub fn track(&self, page: &Page) {
+        track(&self.owned, page);
+    }
+
+    pub fn release(&self, page: &Page) {
+        release(&self.owned, page);
+    }
+
+    pub fn collected(&self) -> usize {
+        self.collected.load(Ordering::Relaxed)
+    }
+
+    pub fn stop(self) {
+        self.handle.abort();
+    }
+}
+
+impl Drop for TabGcHandle {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(id: &str, url: &str) -> (TargetId, String) {
+        (TargetId::new(id), url.into())
+    }
+
+    #[test]
+    fn selects_aged_untracked_and_released_targets() {
+        let gc = TabGc::new(TabGcOptions { max_age: 1000, ..Default::default() });
+        let start = Instant::now();
+        let pages = || vec![
+            page("stray", "about:blank"),
+            page("control", "chrome://version/"),
+            page("live", "about:blank"),
+            page("released", "about:blank"),
+            page("kept", "chrome://newtab/"),
+            page("user", "https://example.com/")
+        ];
+        {
+            let mut owned = gc.owned.lock().unwrap();
+            owned.insert(TargetId::new("live"), None);
+            owned.insert(TargetId::new("released"), Some(start));
+            owned.insert(TargetId::new("gone"), Some(start));
+        }
+        let keep = [TargetId::new("kept")];
+        assert!(gc.select(pages(), &keep, start).is_empty());
+        assert!(!gc.owned.lock().unwrap().contains_key(&TargetId::new("gone")));
+
+        let mut selected = gc.select(pages(), &keep, start + Duration::from_millis(1500));
+        selected.sort_by(|a, b| a.as_ref().cmp(b.as_ref()));
+        assert_eq!(selected, [TargetId::new("control"), TargetId::new("released"), TargetId::new("stray")]);
+    }
+}
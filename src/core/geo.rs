@@ -0,0 +1,174 @@
+use std::{collections::BTreeMap, future::Future};
+use chromiumoxide::{
+    cdp::browser_protocol::emulation::{SetLocaleOverrideParams, SetTimezoneOverrideParams},
+    Page
+};
+
+use crate::error::BrowserError;
+use super::browser::{BrowserSession, PageParam};
+use super::proxy::ProxyRotator;
+
+
+pub static COUNTRY_PROFILES: [(&str, &str, &str); 20] = [
+    ("US", "America/New_York", "en-US"),
+    ("CA", "America/Toronto", "en-CA"),
+    ("MX", "America/Mexico_City", "es-MX"),
+    ("BR", "America/Sao_Paulo", "pt-BR"),
+    ("GB", "Europe/London", "en-GB"),
+    ("DE", "Europe/Berlin", "de-DE"),
+    ("FR", "Europe/Paris", "fr-FR"),
+    ("ES", "Europe/Madrid", "es-ES"),
+    ("IT", "Europe/Rome", "it-IT"),
+    ("NL", "Europe/Amsterdam", "nl-NL"),
+    ("PL", "Europe/Warsaw", "pl-PL"),
+    ("SE", "Europe/Stockholm", "sv-SE"),
+    ("TR", "Europe/Istanbul", "tr-TR"),
+    ("RU", "Europe/Moscow", "ru-RU"),
+    ("IN", "Asia/Kolkata", "en-IN"),
+    ("SG", "Asia/Singapore", "en-SG"),
+    ("JP", "Asia/Tokyo", "ja-JP"),
+    ("KR", "Asia/Seoul", "ko-KR"),
+    ("CN", "Asia/Shanghai", "zh-CN"),
+    ("AU", "Australia/Sydney", "en-AU"),
+];
+
+pub static EU_COUNTRIES: [&str; 27] = [
+    "AT", "BE", "BG", "HR", "CY", "CZ", "DK", "EE", "FI", "FR", "DE", "GR", "HU", "IE",
+    "IT", "LV", "LT", "LU", "MT", "NL", "PL", "PT", "RO", "SK", "SI", "ES", "SE"
+];
+
+pub fn is_gdpr_country(cc: &str) -> bool {
+    EU_COUNTRIES.iter().any(|c| c.eq_ignore_ascii_case(cc))
+        || ["GB", "IS", "LI", "NO"].iter().any(|c| c.eq_ignore_ascii_case(cc))
+}
+
+pub struct GeoVariant<T> {
+    pub country: String,
+    pub proxy: Option<String>,
+    pub result: Result<T, BrowserError>,
+}
+
+pub struct GdprComparison<T> {
+    pub gdpr: GeoVariant<T>,
+    pub non_gdpr: GeoVariant<T>,
+}
+
+pub fn country_profile(cc: &str) -> Option<(&'static str, &'static str)> {
+    COUNTRY_PROFILES.iter()
+        .find(|(code, _, _)| code.eq_ignore_ascii_case(cc))
+        .map(|(_, timezone, locale)| (*timezone, *locale))
+}
+
+pub async fn apply_geo(page: &Page, cc: &str) -> Result<(), BrowserError> {
+    let (timezone, locale) = country_profile(cc)
+        .ok_or(BrowserError::UnknownCountry)?;
+    page.emulate_timezone(SetTimezoneOverrideParams::new(timezone)).await?;
+    page.emulate_locale(
+        SetLocaleOverrideParams::builder()
+            .locale(locale)
+            .build()
+    ).await?;
+    Ok(())
+}
+
+fn accept_language(cc: &str) -> Option<String> {
+    let (_, locale) = country_profile(cc)?;
+    let language = locale.split('-').next().unwrap_or(locale);
+    Some(format!("{locale},{language};q=0.9"))
+}
+
+impl BrowserSession {
+    pub async fn probe_geo<T, F, Fut>(
+        &self,
+        url: &str,
+        countries: &[&str],
+        rotator: &mut ProxyRotator,
+        extractor: F
+    ) -> Result<BTreeMap<String, Result<T, BrowserError>>, BrowserError>
+    where
+        F: Fn(Page) -> Fut,
+        Fut: Future<Output = Result<T, BrowserError>>
+    {
+        let mut results = BTreeMap::new();
+        for cc in countries {
+            let proxy = rotator.next_for_country(cc).map(str::to_owned);
+            let variant = self.visit_as(url, cc, proxy, &extractor).await;
+            results.insert(variant.country, variant.result);
+        }
+
+        Ok(results)
+    }
+
+    async fn visit_as<T, F, Fut>(
+        &self, url: &str, cc: &str, proxy: Option<String>, extractor: &F
+    ) -> GeoVariant<T>
+    where
+        F: Fn(Page) -> Fut,
+        Fut: Future<Output = Result<T, BrowserError>>
+    {
+        let Some(proxy) = proxy else {
+            return GeoVariant {
+                country: cc.to_owned(),
+                proxy: None,
+                result: Err(BrowserError::ProxyNotFound)
+            };
+        };
+        let accept_language = accept_language(cc);
+        let param = PageParam {
+            proxy: Some(&proxy),
+            extra_headers: accept_language.as_deref()
+                .map(|value| vec![("Accept-Language", value)])
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+        let result = match self.new_page_with(&param).await {
+            Ok(page) => {
+                let result = match apply_geo(&page, cc).await {
+                    Ok(_) => match self.open_on_page(url, &page).await {
+                        Ok(_) => extractor(page.clone()).await,
+                        Err(e) => Err(e)
+                    },
+                    Err(e) => Err(e)
+                };
+                let _ = page.close().await;
+                result
+            },
+            Err(e) => Err(e)
+        };
+
+        GeoVariant {
+            country: cc.to_owned(),
+            proxy: Some(proxy),
+            result
+        }
+    }
+
+    pub async fn compare_gdpr<T, F, Fut>(
+        &self,
+        url: &str,
+        rotator: &mut ProxyRotator,
+        non_gdpr_country: &str,
+        extractor: F
+    ) -> Result<GdprComparison<T>, BrowserError>
+    where
+        F: Fn(Page) -> Fut,
+        Fut: Future<Output = Result<T, BrowserError>>
+    {
+        if is_gdpr_country(non_gdpr_country) {
+            return Err(BrowserError::InvalidArgument(
+                format!("{non_gdpr_country} is a GDPR country")
+            ));
+        }
+        let eu_proxy = EU_COUNTRIES.iter()
+            .filter(|cc| country_profile(cc).is_some())
+            .find_map(|cc| rotator.next_for_country(cc).map(|p| (*cc, p.to_owned())));
+        let gdpr = match eu_proxy {
+            Some((cc, proxy)) => self.visit_as(url, cc, Some(proxy), &extractor).await,
+            None => self.visit_as(url, "DE", None, &extractor).await
+        };
+        let proxy = rotator.next_for_country(non_gdpr_country).map(str::to_owned);
+        let non_gdpr = self.visit_as(url, non_gdpr_country, proxy, &extractor).await;
+
+        Ok(GdprComparison { gdpr, non_gdpr })
+    }
+}
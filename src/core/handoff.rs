@@ -0,0 +1,111 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::mpsc,
+    time::timeout
+};
+use tokio_stream::StreamExt;
+use chromiumoxide::{
+    cdp::browser_protocol::{
+        input::InsertTextParams,
+        page::RemoveScriptToEvaluateOnNewDocumentParams
+    },
+    layout::Point,
+    Page
+};
+
+use crate::error::BrowserError;
+use super::bindings::{binding_payloads, unique_binding_name};
+use super::browser::BrowserSession;
+
+
+const HANDOFF_BANNER_JS: &str = r##"
+(() => {
+    if (window.top !== window) return;
+    const show = () => {
+        if (document.getElementById('__bb_handoff')) return;
+        const banner = document.createElement('div');
+        banner.id = '__bb_handoff';
+        banner.style.cssText = 'position:fixed;top:0;left:0;right:0;z-index:2147483647;'
+            + 'display:flex;gap:12px;align-items:center;justify-content:center;padding:10px;'
+            + 'background:#b91c1c;color:#fff;font:14px sans-serif;box-shadow:0 2px 6px rgba(0,0,0,.4)';
+        const text = document.createElement('span');
+        text.textContent = 'Automation paused: ' + __REASON__;
+        const button = document.createElement('button');
+        button.textContent = 'Continue';
+        button.style.cssText = 'padding:4px 12px;cursor:pointer';
+        button.onclick = () => {
+            banner.remove();
+            window[__BINDING__]('done');
+        };
+        banner.append(text, button);
+        document.documentElement.appendChild(banner);
+    };
+    if (document.documentElement) show();
+    else document.addEventListener('DOMContentLoaded', show);
+})()
+"##;
+
+const HANDOFF_REMOVE_JS: &str = "(() => { const b = document.getElementById('__bb_handoff'); if (b) b.remove(); })()";
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum OperatorCommand {
+    Click { x: f64, y: f64 },
+    Type { text: String },
+    Navigate { url: String },
+    Resume,
+}
+
+pub async fn apply_operator_command(page: &Page, command: &OperatorCommand) -> Result<(), BrowserError> {
+    match command {
+        OperatorCommand::Click { x, y } => {
+            page.click(Point { x: *x, y: *y }).await?;
+        },
+        OperatorCommand::Type { text } => {
+            page.execute(InsertTextParams::new(text.clone())).await?;
+        },
+        OperatorCommand::Navigate { url } => {
+            page.goto(url.as_str()).await?;
+        },
+        OperatorCommand::Resume => {}
+    }
+    Ok(())
+}
+
+impl BrowserSession {
+    pub async fn pause_for_human(&self, page: &Page, reason: &str, wait: Duration) -> Result<(), BrowserError> {
+        let name = unique_binding_name("handoff");
+        let mut resumed = Box::pin(binding_payloads(page, &name).await?);
+        let reason = serde_json::to_string(reason)
+            .map_err(|_| BrowserError::Serialization)?;
+        let script = HANDOFF_BANNER_JS
+            .replace("__REASON__", &reason)
+            .replace("__BINDING__", &format!("'{name}'"));
+        let identifier = page.evaluate_on_new_document(script.as_str()).await?;
+        page.evaluate(script).await?;
+        page.bring_to_front().await?;
+        let result = timeout(wait, resumed.next()).await;
+        let _ = page.execute(RemoveScriptToEvaluateOnNewDocumentParams::new(identifier)).await;
+        let _ = page.evaluate(HANDOFF_REMOVE_JS).await;
+
+        match result {
+            Ok(Some(_)) => Ok(()),
+            Ok(None) => Err(BrowserError::HandoffClosed),
+            Err(e) => Err(e.into())
+        }
+    }
+
+    pub async fn operator_control(
+        &self, page: &Page, mut commands: mpsc::Receiver<OperatorCommand>, idle: Duration
+    ) -> Result<(), BrowserError> {
+        page.bring_to_front().await?;
+        while let Some(command) = timeout(idle, commands.recv()).await? {
+            if let OperatorCommand::Resume = command {
+                break;
+            }
+            apply_operator_command(page, &command).await?;
+        }
+        Ok(())
+    }
+}
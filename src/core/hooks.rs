@@ -0,0 +1,199 @@
+use std::{
+    any::Any,
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{Arc, Mutex}
+};
+use tokio::task::JoinHandle;
+use tokio_stream::StreamExt;
+use chromiumoxide::{
+    cdp::{
+        browser_protocol::target::{
+            DetachFromTargetParams,
+            EventAttachedToTarget,
+            SessionId,
+            SetAutoAttachParams
+        },
+        js_protocol::runtime::RunIfWaitingForDebuggerParams
+    },
+    Browser,
+    Page
+};
+
+use crate::error::BrowserError;
+use super::browser::BrowserSession;
+
+
+pub type HookFuture = Pin<Box<dyn Future<Output = Result<(), BrowserError>> + Send>>;
+
+pub type PageHook = Arc<dyn Fn(Page) -> HookFuture + Send + Sync>;
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    payload.downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".into())
+}
+
+pub(crate) fn catch_panic<T>(callback: impl FnOnce() -> T) -> Result<T, BrowserError> {
+    panic::catch_unwind(AssertUnwindSafe(callback))
+        .map_err(|payload| BrowserError::CallbackPanicked(panic_message(payload)))
+}
+
+struct AbortOnDrop<T>(JoinHandle<T>);
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+pub(crate) async fn isolate<T, Fut>(future: Fut) -> Result<T, BrowserError>
+where
+    T: Send + 'static,
+    Fut: Future<Output = Result<T, BrowserError>> + Send + 'static
+{
+    let mut task = AbortOnDrop(tokio::task::spawn(future));
+    match (&mut task.0).await {
+        Ok(result) => result,
+        Err(e) if e.is_panic() => Err(BrowserError::CallbackPanicked(panic_message(e.into_panic()))),
+        Err(_) => Err(BrowserError::Unknown)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct PageHooks {
+    hooks: Arc<Mutex<Vec<PageHook>>>,
+    popup_watcher: Arc<Mutex<Option<JoinHandle<()>>>>,
+}
+
+impl PageHooks {
+    pub fn push(&self, hook: PageHook) {
+        self.hooks.lock().unwrap().push(hook);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.lock().unwrap().is_empty()
+    }
+
+    pub async fn run(&self, page: &Page) -> Result<(), BrowserError> {
+        let hooks = self.hooks.lock().unwrap().clone();
+        for hook in hooks {
+            isolate(hook(page.clone())).await?;
+        }
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        if let Some(handle) = self.popup_watcher.lock().unwrap().take() {
+            handle.abort();
+        }
+    }
+}
+
+async fn resume(browser: &Browser, session_id: SessionId) {
+    let _ = browser.execute(
+        DetachFromTargetParams::builder()
+            .session_id(session_id)
+            .build()
+    ).await;
+}
+
+async fn hook_popup(browser: Arc<Browser>, hooks: PageHooks, event: Arc<EventAttachedToTarget>) {
+    let Ok(page) = browser.get_page(event.target_info.target_id.clone()).await else {
+        return resume(&browser, event.session_id.clone()).await;
+    };
+    let _ = hooks.run(&page).await;
+    // the page handle may be bound to the session the watcher attached
+    // itself, in which case the paused one has to be released separately
+    if page.session_id() == &event.session_id {
+        let _ = page.execute(RunIfWaitingForDebuggerParams::default()).await;
+    } else {
+        resume(&browser, event.session_id.clone()).await;
+    }
+}
+
+async fn watch_popups(ws_url: String, hooks: PageHooks) -> Result<(), BrowserError> {
+    let (browser, mut handler) = Browser::connect(ws_url).await?;
+    let browser = Arc::new(browser);
+    let handle = tokio::task::spawn(async move {
+        while handler.next().await.is_some() {}
+    });
+    let mut attached = browser.event_listener::<EventAttachedToTarget>().await?;
+    browser.execute(
+        SetAutoAttachParams::builder()
+            .auto_attach(true)
+            .wait_for_debugger_on_start(true)
+            .flatten(true)
+            .build()
+            .map_err(|_| BrowserError::ChromeInternal)?
+    ).await?;
+    while let Some(event) = attached.next().await {
+        if !event.waiting_for_debugger {
+            continue;
+        }
+        let info = &event.target_info;
+        if info.r#type != "page" || info.opener_id.is_none() {
+            resume(&browser, event.session_id.clone()).await;
+            continue;
+        }
+        tokio::task::spawn(
+            hook_popup(browser.clone(), hooks.clone(), event.clone())
+        );
+    }
+    handle.abort();
+    Ok(())
+}
+
+impl BrowserSession {
+    pub fn on_new_page<F, Fut>(&self, hook: F)
+    where
+        F: Fn(Page) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), BrowserError>> + Send + 'static
+    {
+        self.page_hooks.push(
+            Arc::new(move |page| Box::pin(hook(page)) as HookFuture)
+        );
+        let mut watcher = self.page_hooks.popup_watcher.lock().unwrap();
+        if watcher.is_none() {
+            let ws_url = self.browser.websocket_address().clone();
+            let hooks = self.page_hooks.clone();
+            *watcher = Some(tokio::task::spawn(async move {
+                let _ = watch_popups(ws_url, hooks).await;
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicBool, Ordering},
+        time::Duration
+    };
+    use tokio::time::{sleep, timeout};
+    use super::*;
+
+    #[tokio::test]
+    async fn panicking_callback_becomes_an_error() {
+        let result = isolate(async { panic!("extractor {}", 7) as Result<(), BrowserError> }).await;
+        assert!(matches!(result, Err(BrowserError::CallbackPanicked(m)) if m == "extractor 7"));
+        assert!(matches!(catch_panic(|| 1), Ok(1)));
+    }
+
+    #[tokio::test]
+    async fn dropping_the_caller_aborts_the_callback() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let flag = finished.clone();
+        let caller = isolate(async move {
+            sleep(Duration::from_millis(50)).await;
+            flag.store(true, Ordering::SeqCst);
+            Ok(())
+        });
+        let cancelled = timeout(Duration::from_millis(10), caller).await;
+        assert!(cancelled.is_err());
+        sleep(Duration::from_millis(100)).await;
+        assert!(!finished.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,299 @@
+use std::{
+    fs,
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH}
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use chromiumoxide::cdp::browser_protocol::{
+    network::{Cookie, CookieParam, TimeSinceEpoch},
+    storage::GetCookiesParams
+};
+
+use crate::error::BrowserError;
+use super::browser::BrowserSession;
+use super::context::{BrowserContextHandle, ContextParam};
+use super::fingerprint::{DeviceKind, FingerprintProfile, CHROME_MAJOR};
+
+
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+pub fn cookie_to_param(cookie: &Cookie) -> CookieParam {
+    let mut param = CookieParam::new(cookie.name.clone(), cookie.value.clone());
+    param.domain = Some(cookie.domain.clone());
+    param.path = Some(cookie.path.clone());
+    param.secure = Some(cookie.secure);
+    param.http_only = Some(cookie.http_only);
+    param.same_site = cookie.same_site.clone();
+    param.priority = Some(cookie.priority.clone());
+    param.partition_key = cookie.partition_key.clone();
+    if !cookie.session && cookie.expires > 0.0 {
+        param.expires = Some(TimeSinceEpoch::new(cookie.expires));
+    }
+    param
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Identity {
+    pub name: String,
+    pub fingerprint: FingerprintProfile,
+    pub user_agent: String,
+    pub cookies: Vec<CookieParam>,
+    pub proxy: Option<String>,
+    pub created_at: u64,
+    pub last_used: Option<u64>,
+    #[serde(default)]
+    pub requests: u64,
+    #[serde(default)]
+    pub blocks: u32,
+}
+
+impl Identity {
+    pub fn new(name: &str, fingerprint: FingerprintProfile) -> Self {
+        Self {
+            name: name.into(),
+            user_agent: fingerprint.user_agent(CHROME_MAJOR),
+            fingerprint,
+            cookies: Vec::new(),
+            proxy: None,
+            created_at: unix_now(),
+            last_used: None,
+            requests: 0,
+            blocks: 0
+        }
+    }
+
+    pub fn context_param(&self) -> ContextParam {
+        ContextParam {
+            proxy: self.proxy.clone(),
+            user_agent: Some(self.user_agent.clone()),
+            cookies: self.cookies.clone(),
+            fingerprint: Some(self.fingerprint.clone()),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct IdentityStore {
+    dir: PathBuf,
+}
+
+impl IdentityStore {
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Self> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Self { dir: dir.as_ref().to_path_buf() })
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        let mut file: String = name.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        if file != name {
+            let hash = name.bytes()
+                .fold(0x811c9dc5_u32, |h, b| (h ^ b as u32).wrapping_mul(0x01000193));
+            file = format!("{file}-{hash:08x}");
+        }
+        self.dir.join(format!("{file}.json"))
+    }
+
+    pub fn list(&self) -> io::Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(name) = path.file_stem().and_then(|n| n.to_str()) {
+                names.push(name.to_owned());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    pub fn load(&self, name: &str) -> io::Result<Identity> {
+        let data = fs::read(self.path(name))?;
+        serde_json::from_slice(&data)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, identity: &Identity) -> io::Result<()> {
+        let path = self.path(&identity.name);
+        let tmp = path.with_extension("json.tmp");
+        let data = serde_json::to_vec_pretty(identity)
+            .map_err(io::Error::other)?;
+        fs::write(&tmp, data)?;
+        fs::rename(&tmp, &path)
+    }
+
+    pub fn remove(&self, name: &str) -> io::Result<()> {
+        fs::remove_file(self.path(name))
+    }
+}
+
+impl BrowserContextHandle<'_> {
+    pub async fn cookies(&self) -> Result<Vec<Cookie>, BrowserError> {
+        let params = GetCookiesParams::builder()
+            .browser_context_id(self.id().clone())
+            .build();
+        Ok(self.session().browser.execute(params).await?.result.cookies)
+    }
+}
+
+impl BrowserSession {
+    pub async fn open_identity(&self, identity: &mut Identity) -> Result<BrowserContextHandle<'_>, BrowserError> {
+        identity.last_used = Some(unix_now());
+        identity.user_agent = identity.fingerprint.user_agent(self.chrome_major().await?);
+        self.new_context(identity.context_param()).await
+    }
+
+    pub async fn sync_identity(
+        &self, context: &BrowserContextHandle<'_>, identity: &mut Identity
+    ) -> Result<(), BrowserError> {
+        identity.cookies = context.cookies().await?
+            .iter()
+            .map(cookie_to_param)
+            .collect();
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct IdentityPolicy {
+    pub max_requests: Option<u64>,
+    pub max_age: Option<Duration>,
+    pub max_blocks: Option<u32>,
+    pub inventory_size: usize,
+    pub device: DeviceKind,
+    pub proxies: Vec<String>,
+}
+
+impl Default for IdentityPolicy {
+    fn default() -> Self {
+        Self {
+            max_requests: Some(500),
+            max_age: Some(Duration::from_secs(30 * 24 * 3600)),
+            max_blocks: Some(3),
+            inventory_size: 10,
+            device: DeviceKind::Desktop,
+            proxies: Vec::new()
+        }
+    }
+}
+
+impl IdentityPolicy {
+    pub fn is_healthy(&self, identity: &Identity) -> bool {
+        let age = unix_now().saturating_sub(identity.created_at);
+        self.max_requests.is_none_or(|max| identity.requests < max)
+            && self.max_blocks.is_none_or(|max| identity.blocks < max)
+            && self.max_age.is_none_or(|max| age < max.as_secs())
+    }
+}
+
+pub struct IdentityRotator {
+    store: IdentityStore,
+    policy: IdentityPolicy,
+}
+
+impl IdentityRotator {
+    pub fn new(store: IdentityStore, policy: IdentityPolicy) -> Self {
+        Self { store, policy }
+    }
+
+    pub fn store(&self) -> &IdentityStore {
+        &self.store
+    }
+
+    pub fn policy(&self) -> &IdentityPolicy {
+        &self.policy
+    }
+
+    pub fn provision(&self) -> io::Result<Identity> {
+        let mut rng = rand::thread_rng();
+        let name = format!("identity-{}-{:08x}", unix_now(), rng.gen::<u32>());
+        let mut identity = Identity::new(
+            &name, FingerprintProfile::for_device(self.policy.device)
+        );
+        if !self.policy.proxies.is_empty() {
+            let index = rng.gen_range(0..self.policy.proxies.len());
+            identity.proxy = Some(self.policy.proxies[index].clone());
+        }
+        self.store.save(&identity)?;
+        Ok(identity)
+    }
+
+    pub fn retire(&self, name: &str) -> io::Result<()> {
+        self.store.remove(name)
+    }
+
+    pub fn inventory(&self) -> io::Result<Vec<Identity>> {
+        let mut healthy = Vec::new();
+        for name in self.store.list()? {
+            let Ok(identity) = self.store.load(&name) else {
+                continue;
+            };
+            if self.policy.is_healthy(&identity) {
+                healthy.push(identity);
+            }
+        }
+        Ok(healthy)
+    }
+
+    pub fn rotate(&self) -> io::Result<Vec<String>> {
+        let mut retired = Vec::new();
+        let mut healthy = 0;
+        for name in self.store.list()? {
+            let Ok(identity) = self.store.load(&name) else {
+                continue;
+            };
+            if self.policy.is_healthy(&identity) {
+                healthy += 1;
+            } else {
+                self.retire(&name)?;
+                retired.push(name);
+            }
+        }
+        for _ in healthy..self.policy.inventory_size {
+            self.provision()?;
+        }
+        Ok(retired)
+    }
+
+    pub fn next_identity(&self) -> io::Result<Identity> {
+        self.rotate()?;
+        let identity = self.inventory()?
+            .into_iter()
+            .min_by_key(|i| i.last_used.unwrap_or(0));
+        match identity {
+            Some(identity) => Ok(identity),
+            None => self.provision()
+        }
+    }
+
+    pub fn record_request(&self, identity: &mut Identity) -> io::Result<bool> {
+        identity.requests += 1;
+        self.record(identity)
+    }
+
+    pub fn record_block(&self, identity: &mut Identity) -> io::Result<bool> {
+        identity.blocks += 1;
+        self.record(identity)
+    }
+
+    fn record(&self, identity: &Identity) -> io::Result<bool> {
+        if self.policy.is_healthy(identity) {
+            self.store.save(identity)?;
+            return Ok(true);
+        }
+        self.retire(&identity.name)?;
+        Ok(false)
+    }
+}
@@ -0,0 +1,600 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex}
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    runtime::Handle,
+    sync::mpsc,
+    task::JoinHandle
+};
+use tokio_stream::StreamExt;
+use chromiumoxide::{
+    cdp::browser_protocol::{
+        fetch::{
+            AuthChallengeResponse,
+            AuthChallengeResponseResponse,
+            AuthChallengeSource,
+            ContinueRequestParams,
+            ContinueWithAuthParams,
+            DisableParams,
+            EnableParams,
+            EventAuthRequired,
+            EventRequestPaused,
+            FailRequestParams,
+            HeaderEntry,
+            RequestPattern,
+            RequestStage
+        },
+        network::{
+            ErrorReason,
+            EventLoadingFinished,
+            EventResponseReceived,
+            GetResponseBodyParams,
+            Headers,
+            ResourceType
+        },
+        target::TargetId
+    },
+    Page
+};
+
+use crate::error::BrowserError;
+use super::browser::{BrowserSession, PageParam};
+use super::hooks::catch_panic;
+
+
+pub static TRACKER_BLOCKLIST: [&str; 14] = [
+    "google-analytics.com",
+    "googletagmanager.com",
+    "googlesyndication.com",
+    "doubleclick.net",
+    "connect.facebook.net",
+    "analytics.tiktok.com",
+    "mc.yandex.ru",
+    "hotjar.com",
+    "scorecardresearch.com",
+    "criteo.com",
+    "adnxs.com",
+    "taboola.com",
+    "outbrain.com",
+    "quantserve.com"
+];
+
+fn headers_to_vec(headers: &Headers) -> Vec<(String, String)> {
+    headers.inner()
+        .as_object()
+        .map(|map| map.iter()
+            .map(|(k, v)| (k.clone(), v.as_str().unwrap_or_default().to_owned()))
+            .collect()
+        )
+        .unwrap_or_default()
+}
+
+#[derive(Clone, Debug)]
+pub struct InterceptedRequest {
+    pub url: String,
+    pub method: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>,
+    pub resource_type: ResourceType,
+}
+
+impl InterceptedRequest {
+    fn from_event(event: &EventRequestPaused) -> Self {
+        let headers = headers_to_vec(&event.request.headers);
+        let body = event.request.post_data_entries.as_ref().map(|entries|
+            entries.iter()
+                .filter_map(|e| e.bytes.as_ref())
+                .filter_map(|b| STANDARD.decode(AsRef::<str>::as_ref(b)).ok())
+                .flatten()
+                .collect()
+        );
+        Self {
+            url: event.request.url.clone(),
+            method: event.request.method.clone(),
+            headers,
+            body,
+            resource_type: event.resource_type.clone()
+        }
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn set_header(&mut self, name: &str, value: impl Into<String>) {
+        self.headers.retain(|(k, _)| !k.eq_ignore_ascii_case(name));
+        self.headers.push((name.to_owned(), value.into()));
+    }
+}
+
+pub type RequestHook = Arc<dyn Fn(&mut InterceptedRequest) + Send + Sync>;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProxyAuth {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Clone, Default)]
+pub struct InterceptConfig {
+    pub request_hook: Option<RequestHook>,
+    pub block_resources: Vec<ResourceType>,
+    pub url_blocklist: Vec<String>,
+    pub proxy_auth: Option<ProxyAuth>,
+}
+
+impl InterceptConfig {
+    pub fn is_active(&self) -> bool {
+        self.request_hook.is_some()
+            || !self.block_resources.is_empty()
+            || !self.url_blocklist.is_empty()
+            || self.proxy_auth.is_some()
+    }
+
+    fn blocks(&self, event: &EventRequestPaused) -> bool {
+        self.block_resources.contains(&event.resource_type)
+            || self.url_blocklist.iter().any(|p| event.request.url.contains(p.as_str()))
+    }
+}
+
+impl<'a> From<&PageParam<'a>> for InterceptConfig {
+    fn from(param: &PageParam<'a>) -> Self {
+        Self {
+            request_hook: None,
+            block_resources: param.block_resources.clone(),
+            url_blocklist: param.url_blocklist.iter()
+                .map(|p| p.to_string())
+                .collect(),
+            proxy_auth: None
+        }
+    }
+}
+
+fn run_request_hooks(hooks: &[&RequestHook], request: &mut InterceptedRequest) -> Result<(), BrowserError> {
+    for hook in hooks {
+        catch_panic(|| hook(request))?;
+    }
+    Ok(())
+}
+
+async fn continue_request(page: &Page, event: &EventRequestPaused, configs: &[InterceptConfig]) -> Result<(), BrowserError> {
+    let mut params = ContinueRequestParams::new(event.request_id.clone());
+    let hooks: Vec<&RequestHook> = configs.iter()
+        .filter_map(|c| c.request_hook.as_ref())
+        .collect();
+    if !hooks.is_empty() {
+        let original = InterceptedRequest::from_event(event);
+        let mut request = original.clone();
+        // a panicking hook must not leave the request paused
+        if let Err(e) = run_request_hooks(&hooks, &mut request) {
+            page.execute(params).await?;
+            return Err(e);
+        }
+        params.url = Some(request.url);
+        params.method = Some(request.method);
+        params.headers = Some(
+            request.headers.into_iter()
+                .map(|(name, value)| HeaderEntry::new(name, value))
+                .collect()
+        );
+        if request.body != original.body {
+            params.post_data = request.body
+                .map(|b| STANDARD.encode(b).into());
+        }
+    }
+    page.execute(params).await?;
+    Ok(())
+}
+
+async fn handle_paused(page: &Page, event: &EventRequestPaused, configs: &[InterceptConfig]) -> Result<(), BrowserError> {
+    if configs.iter().any(|c| c.blocks(event)) {
+        page.execute(
+            FailRequestParams::new(event.request_id.clone(), ErrorReason::BlockedByClient)
+        ).await?;
+        return Ok(());
+    }
+    continue_request(page, event, configs).await
+}
+
+async fn handle_auth(page: &Page, event: &EventAuthRequired, proxy_auth: Option<ProxyAuth>) -> Result<(), BrowserError> {
+    let response = match (proxy_auth, &event.auth_challenge.source) {
+        (Some(auth), Some(AuthChallengeSource::Proxy)) => AuthChallengeResponse {
+            response: AuthChallengeResponseResponse::ProvideCredentials,
+            username: Some(auth.username),
+            password: Some(auth.password)
+        },
+        _ => AuthChallengeResponse::new(AuthChallengeResponseResponse::Default)
+    };
+    page.execute(
+        ContinueWithAuthParams::new(event.request_id.clone(), response)
+    ).await?;
+    Ok(())
+}
+
+fn is_response_stage(event: &EventRequestPaused) -> bool {
+    event.response_status_code.is_some() || event.response_error_reason.is_some()
+}
+
+enum FetchLayer {
+    Request(InterceptConfig),
+    Response(ResourceType, mpsc::UnboundedSender<EventRequestPaused>),
+}
+
+static FETCH_OWNERS: Lazy<Mutex<HashMap<TargetId, Arc<FetchOwner>>>> = Lazy::new(
+    || Mutex::new(HashMap::new())
+);
+
+struct FetchOwner {
+    page: Page,
+    layers: Mutex<Vec<(u64, FetchLayer)>>,
+    next_id: Mutex<u64>,
+    handle: tokio::sync::Mutex<Option<JoinHandle<()>>>,
+}
+
+impl FetchOwner {
+    fn for_page(page: &Page) -> Arc<Self> {
+        FETCH_OWNERS.lock().unwrap()
+            .entry(page.target_id().clone())
+            .or_insert_with(|| Arc::new(Self {
+                page: page.clone(),
+                layers: Mutex::new(Vec::new()),
+                next_id: Mutex::new(0),
+                handle: tokio::sync::Mutex::new(None)
+            }))
+            .clone()
+    }
+
+    fn request_configs(&self) -> Vec<InterceptConfig> {
+        self.layers.lock().unwrap()
+            .iter()
+            .filter_map(|(_, layer)| match layer {
+                FetchLayer::Request(config) => Some(config.clone()),
+                FetchLayer::Response(..) => None
+            })
+            .collect()
+    }
+
+    fn response_subscriber(&self, resource_type: &ResourceType) -> Option<mpsc::UnboundedSender<EventRequestPaused>> {
+        self.layers.lock().unwrap()
+            .iter()
+            .find_map(|(_, layer)| match layer {
+                FetchLayer::Response(t, sender) if t == resource_type && !sender.is_closed() => Some(sender.clone()),
+                _ => None
+            })
+    }
+
+    fn enable_params(&self) -> Option<EnableParams> {
+        let layers = self.layers.lock().unwrap();
+        if layers.is_empty() {
+            return None;
+        }
+        let mut patterns = Vec::new();
+        let mut handle_auth = false;
+        for (_, layer) in layers.iter() {
+            match layer {
+                FetchLayer::Request(config) => {
+                    handle_auth |= config.proxy_auth.is_some();
+                    let pattern = RequestPattern::builder()
+                        .url_pattern("*")
+                        .request_stage(RequestStage::Request)
+                        .build();
+                    if !patterns.contains(&pattern) {
+                        patterns.push(pattern);
+                    }
+                },
+                FetchLayer::Response(resource_type, _) => {
+                    let pattern = RequestPattern::builder()
+                        .resource_type(resource_type.clone())
+                        .request_stage(RequestStage::Response)
+                        .build();
+                    if !patterns.contains(&pattern) {
+                        patterns.push(pattern);
+                    }
+                }
+            }
+        }
+        Some(EnableParams {
+            patterns: Some(patterns),
+            handle_auth_requests: Some(handle_auth)
+        })
+    }
+
+    async fn dispatch(&self, event: EventRequestPaused) {
+        if is_response_stage(&event) {
+            let event = match self.response_subscriber(&event.resource_type) {
+                Some(sender) => match sender.send(event) {
+                    Ok(()) => return,
+                    Err(e) => e.0
+                },
+                None => event
+            };
+            let _ = self.page.execute(ContinueRequestParams::new(event.request_id.clone())).await;
+            return;
+        }
+        let _ = handle_paused(&self.page, &event, &self.request_configs()).await;
+    }
+
+    async fn start(self: &Arc<Self>) -> Result<(), BrowserError> {
+        let mut handle = self.handle.lock().await;
+        if handle.as_ref().is_some_and(|h| !h.is_finished()) {
+            return Ok(());
+        }
+        let mut paused = self.page.event_listener::<EventRequestPaused>().await?;
+        let mut auth_required = self.page.event_listener::<EventAuthRequired>().await?;
+        let owner = self.clone();
+        *handle = Some(tokio::task::spawn(async move {
+            loop {
+                tokio::select! {
+                    Some(event) = paused.next() => {
+                        owner.dispatch((*event).clone()).await;
+                    },
+                    Some(event) = auth_required.next() => {
+                        let proxy_auth = owner.request_configs()
+                            .into_iter()
+                            .find_map(|c| c.proxy_auth);
+                        let _ = handle_auth(&owner.page, &event, proxy_auth).await;
+                    },
+                    else => break
+                }
+            }
+            owner.release();
+        }));
+        Ok(())
+    }
+
+    fn release(self: &Arc<Self>) {
+        let mut owners = FETCH_OWNERS.lock().unwrap();
+        if owners.get(self.page.target_id()).is_some_and(|o| Arc::ptr_eq(o, self)) {
+            owners.remove(self.page.target_id());
+        }
+    }
+
+    async fn sync(self: &Arc<Self>) -> Result<(), BrowserError> {
+        match self.enable_params() {
+            Some(params) => {
+                self.start().await?;
+                self.page.execute(params).await?;
+            },
+            None => {
+                self.page.execute(DisableParams::default()).await?;
+                if self.layers.lock().unwrap().is_empty() {
+                    self.release();
+                    if let Some(handle) = self.handle.lock().await.take() {
+                        handle.abort();
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn push(&self, layer: FetchLayer) -> u64 {
+        let mut next_id = self.next_id.lock().unwrap();
+        *next_id += 1;
+        self.layers.lock().unwrap().push((*next_id, layer));
+        *next_id
+    }
+
+    fn remove(&self, id: u64) {
+        self.layers.lock().unwrap().retain(|(layer_id, _)| *layer_id != id);
+    }
+}
+
+pub(crate) struct FetchRegistration {
+    owner: Arc<FetchOwner>,
+    id: Option<u64>,
+}
+
+impl FetchRegistration {
+    async fn register(page: &Page, layer: FetchLayer) -> Result<Self, BrowserError> {
+        let owner = FetchOwner::for_page(page);
+        let id = owner.push(layer);
+        let registration = Self {
+            owner,
+            id: Some(id)
+        };
+        registration.owner.sync().await?;
+        Ok(registration)
+    }
+
+    fn pin(mut self) {
+        self.id = None;
+    }
+}
+
+impl Drop for FetchRegistration {
+    fn drop(&mut self) {
+        let Some(id) = self.id.take() else {
+            return;
+        };
+        self.owner.remove(id);
+        let Ok(runtime) = Handle::try_current() else {
+            return;
+        };
+        let owner = self.owner.clone();
+        runtime.spawn(async move {
+            let _ = owner.sync().await;
+        });
+    }
+}
+
+pub(crate) async fn subscribe_responses(
+    page: &Page,
+    resource_type: ResourceType
+) -> Result<(FetchRegistration, mpsc::UnboundedReceiver<EventRequestPaused>), BrowserError> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let registration = FetchRegistration::register(
+        page, FetchLayer::Response(resource_type, sender)
+    ).await?;
+    Ok((registration, receiver))
+}
+
+pub struct Interceptor {
+    registration: FetchRegistration,
+}
+
+impl Interceptor {
+    pub async fn start(page: &Page, config: InterceptConfig) -> Result<Self, BrowserError> {
+        let registration = FetchRegistration::register(
+            page, FetchLayer::Request(config)
+        ).await?;
+
+        Ok (
+            Self {
+                registration
+            }
+        )
+    }
+
+    pub fn detach(self) {
+        self.registration.pin();
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NetworkResponse {
+    pub url: String,
+    pub status: i64,
+    pub mime_type: String,
+    pub headers: Vec<(String, String)>,
+    pub resource_type: ResourceType,
+    pub body: Option<Vec<u8>>,
+    #[serde(default)]
+    pub raw_body: bool,
+}
+
+pub struct ResponseCapture {
+    responses: Arc<Mutex<Vec<NetworkResponse>>>,
+    handle: JoinHandle<()>,
+}
+
+async fn response_body(page: &Page, event: &EventLoadingFinished) -> Option<(Vec<u8>, bool)> {
+    let body = page.execute(
+        GetResponseBodyParams::new(event.request_id.clone())
+    ).await.ok()?.result;
+    match body.base64_encoded {
+        true => STANDARD.decode(&body.body).ok().map(|b| (b, true)),
+        false => Some((body.body.into_bytes(), false))
+    }
+}
+
+impl ResponseCapture {
+    pub async fn start<F>(page: &Page, matcher: F) -> Result<Self, BrowserError>
+    where
+        F: Fn(&str, &ResourceType) -> bool + Send + 'static
+    {
+        let mut received = page.event_listener::<EventResponseReceived>().await?;
+        let mut finished = page.event_listener::<EventLoadingFinished>().await?;
+        let responses = Arc::new(Mutex::new(Vec::new()));
+        let task_responses = responses.clone();
+        let task_page = page.clone();
+        let handle = tokio::task::spawn(async move {
+            let mut pending: HashMap<String, NetworkResponse> = HashMap::new();
+            loop {
+                tokio::select! {
+                    Some(event) = received.next() => {
+                        if !matcher(&event.response.url, &event.r#type) {
+                            continue;
+                        }
+                        pending.insert(
+                            event.request_id.inner().clone(),
+                            NetworkResponse {
+                                url: event.response.url.clone(),
+                                status: event.response.status,
+                                mime_type: event.response.mime_type.clone(),
+                                headers: headers_to_vec(&event.response.headers),
+                                resource_type: event.r#type.clone(),
+                                body: None,
+                                raw_body: false
+                            }
+                        );
+                    },
+                    Some(event) = finished.next() => {
+                        let Some(mut response) = pending.remove(event.request_id.inner()) else {
+                            continue;
+                        };
+                        if let Some((body, raw)) = response_body(&task_page, &event).await {
+                            response.body = Some(body);
+                            response.raw_body = raw;
+                        }
+                        task_responses.lock().unwrap().push(response);
+                    },
+                    else => break
+                }
+            }
+        });
+
+        Ok (
+            Self {
+                responses,
+                handle
+            }
+        )
+    }
+
+    pub fn responses(&self) -> Vec<NetworkResponse> {
+        self.responses.lock().unwrap().clone()
+    }
+
+    pub fn stop(self) -> Vec<NetworkResponse> {
+        self.handle.abort();
+        self.responses()
+    }
+}
+
+impl Drop for ResponseCapture {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+impl BrowserSession {
+    pub async fn open_with_capture<F>(&self, url: &str, matcher: F) -> Result<(Page, ResponseCapture), BrowserError>
+    where
+        F: Fn(&str, &ResourceType) -> bool + Send + 'static
+    {
+        let page = self.new_page().await?;
+        let capture = ResponseCapture::start(&page, matcher).await?;
+        self.open_on_page(url, &page).await?;
+
+        Ok((page, capture))
+    }
+
+    pub async fn open_with_intercept(&self, url: &str, config: InterceptConfig) -> Result<(Page, Interceptor), BrowserError> {
+        let page = self.new_page().await?;
+        let interceptor = Interceptor::start(&page, config).await?;
+        self.open_on_page(url, &page).await?;
+
+        Ok((page, interceptor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panicking_request_hook_is_contained() {
+        let rewrite: RequestHook = Arc::new(|r| r.url = "https://example.org/".into());
+        let broken: RequestHook = Arc::new(|_| panic!("bad hook"));
+        let mut request = InterceptedRequest {
+            url: "https://example.com/".into(),
+            method: "GET".into(),
+            headers: Vec::new(),
+            body: None,
+            resource_type: ResourceType::Document
+        };
+        assert!(run_request_hooks(&[&rewrite], &mut request).is_ok());
+        assert_eq!(request.url, "https://example.org/");
+        match run_request_hooks(&[&rewrite, &broken], &mut request) {
+            Err(BrowserError::CallbackPanicked(message)) => assert_eq!(message, "bad hook"),
+            other => panic!("unexpected {other:?}")
+        }
+    }
+}
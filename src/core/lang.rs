@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+pub use whatlang::Lang;
+
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DetectedLanguage {
+    pub code: String,
+    pub name: String,
+    pub script: String,
+    pub confidence: f64,
+    pub reliable: bool,
+}
+
+pub fn detect_language_info(text: &str) -> Option<DetectedLanguage> {
+    let info = whatlang::detect(text)?;
+    Some (
+        DetectedLanguage {
+            code: info.lang().code().to_owned(),
+            name: info.lang().eng_name().to_owned(),
+            script: info.script().name().to_owned(),
+            confidence: info.confidence(),
+            reliable: info.is_reliable()
+        }
+    )
+}
+
+pub fn detect_language(text: &str) -> Option<Lang> {
+    whatlang::detect_lang(text)
+}
@@ -1,4 +1,82 @@
+mod artifacts;
+mod audit;
+mod bindings;
 mod browser;
+mod cancel;
+mod clock;
+mod config;
+mod content;
+mod context;
+mod download;
+mod encoding;
+mod extract;
+mod fingerprint;
+mod flags;
+mod frontier;
+mod gc;
+mod geo;
+mod handoff;
+mod hooks;
+mod identity;
+mod intercept;
+#[cfg(feature = "lang")]
+mod lang;
+mod network;
+mod page;
+mod pdf;
+mod pool;
+mod profile;
+mod proxy;
+#[cfg(feature = "redis")]
+mod redis_frontier;
+mod retry;
+#[cfg(feature = "s3")]
+mod s3;
+mod screencast;
+mod sink;
+mod storage;
+mod upload;
+mod warmup;
+mod waterfall;
 pub mod extension;
 
+pub use artifacts::*;
+pub use audit::*;
+pub use bindings::*;
 pub use browser::*;
+pub use cancel::*;
+pub use clock::*;
+pub use config::*;
+pub use content::*;
+pub use context::*;
+pub use download::*;
+pub use encoding::*;
+pub use extract::*;
+pub use fingerprint::*;
+pub use flags::*;
+pub use frontier::*;
+pub use gc::*;
+pub use geo::*;
+pub use handoff::*;
+pub use hooks::*;
+pub use identity::*;
+pub use intercept::*;
+#[cfg(feature = "lang")]
+pub use lang::*;
+pub use network::*;
+pub use page::*;
+pub use pdf::*;
+pub use pool::*;
+pub use profile::*;
+pub use proxy::*;
+#[cfg(feature = "redis")]
+pub use redis_frontier::*;
+pub use retry::*;
+#[cfg(feature = "s3")]
+pub use s3::*;
+pub use screencast::*;
+pub use sink::*;
+pub use storage::*;
+pub use upload::*;
+pub use warmup::*;
+pub use waterfall::*;
@@ -0,0 +1,579 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH}
+};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::watch,
+    task::JoinHandle,
+    time::sleep
+};
+use tokio_stream::StreamExt;
+use chromiumoxide::{
+    cdp::browser_protocol::network::{
+        EventDataReceived,
+        EventLoadingFailed,
+        EventLoadingFinished,
+        EventRequestWillBeSent,
+        EventResponseReceived,
+        ResourceType,
+        ResourceTiming,
+        Response,
+        SecurityDetails,
+        SetBlockedUrLsParams
+    },
+    cdp::browser_protocol::page::{FrameResourceTree, GetResourceTreeParams, StopLoadingParams},
+    Page
+};
+
+use crate::error::BrowserError;
+use super::browser::BrowserSession;
+
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EntryTiming {
+    pub request_time: f64,
+    pub dns_start: f64,
+    pub dns_end: f64,
+    pub connect_start: f64,
+    pub connect_end: f64,
+    pub ssl_start: f64,
+    pub ssl_end: f64,
+    pub send_start: f64,
+    pub send_end: f64,
+    pub receive_headers_end: f64,
+}
+
+impl From<&ResourceTiming> for EntryTiming {
+    fn from(timing: &ResourceTiming) -> Self {
+        Self {
+            request_time: timing.request_time,
+            dns_start: timing.dns_start,
+            dns_end: timing.dns_end,
+            connect_start: timing.connect_start,
+            connect_end: timing.connect_end,
+            ssl_start: timing.ssl_start,
+            ssl_end: timing.ssl_end,
+            send_start: timing.send_start,
+            send_end: timing.send_end,
+            receive_headers_end: timing.receive_headers_end
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NetworkEntry {
+    pub request_id: String,
+    pub url: String,
+    pub method: String,
+    pub resource_type: Option<ResourceType>,
+    pub status: Option<i64>,
+    pub mime_type: Option<String>,
+    pub encoded_size: f64,
+    pub started: f64,
+    pub finished: Option<f64>,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub timing: Option<EntryTiming>,
+    #[serde(default)]
+    pub wall_time: Option<f64>,
+}
+
+impl NetworkEntry {
+    fn new(request_id: &str) -> Self {
+        Self {
+            request_id: request_id.to_owned(),
+            url: String::new(),
+            method: String::new(),
+            resource_type: None,
+            status: None,
+            mime_type: None,
+            encoded_size: 0.0,
+            started: 0.0,
+            finished: None,
+            error: None,
+            timing: None,
+            wall_time: None
+        }
+    }
+}
+
+#[derive(Default)]
+struct NetworkLog {
+    entries: Vec<NetworkEntry>,
+    index: HashMap<String, usize>,
+    last_event: Option<Instant>,
+}
+
+impl NetworkLog {
+    fn in_flight(&self) -> usize {
+        self.entries.iter()
+            .filter(|e| e.finished.is_none() && e.error.is_none())
+            .count()
+    }
+
+    fn entry_mut(&mut self, request_id: &str) -> &mut NetworkEntry {
+        self.last_event = Some(Instant::now());
+        let idx = match self.index.get(request_id) {
+            Some(idx) => *idx,
+            None => {
+                self.entries.push(NetworkEntry::new(request_id));
+                self.index.insert(request_id.to_owned(), self.entries.len() - 1);
+                self.entries.len() - 1
+            }
+        };
+        &mut self.entries[idx]
+    }
+}
+
+pub struct NetworkRecorder {
+    log: Arc<Mutex<NetworkLog>>,
+    handle: JoinHandle<()>,
+}
+
+impl NetworkRecorder {
+    pub async fn start(page: &Page) -> Result<Self, BrowserError> {
+        let mut requests = page.event_listener::<EventRequestWillBeSent>().await?;
+        let mut responses = page.event_listener::<EventResponseReceived>().await?;
+        let mut finished = page.event_listener::<EventLoadingFinished>().await?;
+        let mut failed = page.event_listener::<EventLoadingFailed>().await?;
+        let log = Arc::new(Mutex::new(NetworkLog::default()));
+        let task_log = log.clone();
+        let handle = tokio::task::spawn(async move {
+            loop {
+                tokio::select! {
+                    Some(event) = requests.next() => {
+                        let mut log = task_log.lock().unwrap();
+                        let entry = log.entry_mut(event.request_id.inner());
+                        entry.url = event.request.url.clone();
+                        entry.method = event.request.method.clone();
+                        entry.started = *event.timestamp.inner();
+                        entry.wall_time = Some(*event.wall_time.inner());
+                        if event.r#type.is_some() {
+                            entry.resource_type = event.r#type.clone();
+                        }
+                    },
+                    Some(event) = responses.next() => {
+                        let mut log = task_log.lock().unwrap();
+                        let entry = log.entry_mut(event.request_id.inner());
+                        entry.status = Some(event.response.status);
+                        entry.mime_type = Some(event.response.mime_type.clone());
+                        entry.resource_type = Some(event.r#type.clone());
+                        entry.timing = event.response.timing.as_ref().map(EntryTiming::from);
+                        if entry.url.is_empty() {
+                            entry.url = event.response.url.clone();
+                        }
+                    },
+                    Some(event) = finished.next() => {
+                        let mut log = task_log.lock().unwrap();
+                        let entry = log.entry_mut(event.request_id.inner());
+                        entry.encoded_size = event.encoded_data_length;
+                        entry.finished = Some(*event.timestamp.inner());
+                    },
+                    Some(event) = failed.next() => {
+                        let mut log = task_log.lock().unwrap();
+                        let entry = log.entry_mut(event.request_id.inner());
+                        entry.error = Some(event.error_text.clone());
+                        entry.finished = Some(*event.timestamp.inner());
+                    },
+                    else => break
+                }
+            }
+        });
+
+        Ok (
+            Self {
+                log,
+                handle
+            }
+        )
+    }
+
+    pub fn entries(&self) -> Vec<NetworkEntry> {
+        self.log.lock().unwrap().entries.clone()
+    }
+
+    pub async fn wait_idle(&self, idle: Duration, max_wait: Duration) {
+        let start = Instant::now();
+        while start.elapsed() < max_wait {
+            let (in_flight, quiet) = {
+                let log = self.log.lock().unwrap();
+                let quiet = log.last_event.unwrap_or(start).elapsed() >= idle;
+                (log.in_flight(), quiet)
+            };
+            if in_flight == 0 && quiet {
+                return;
+            }
+            sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    pub async fn drain(self, idle: Duration, max_wait: Duration) -> Vec<NetworkEntry> {
+        self.wait_idle(idle, max_wait).await;
+        self.stop()
+    }
+
+    pub fn stop(self) -> Vec<NetworkEntry> {
+        self.handle.abort();
+        self.entries()
+    }
+}
+
+impl Drop for NetworkRecorder {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct JobBudget {
+    pub max_requests: Option<usize>,
+    pub max_bytes: Option<f64>,
+    pub max_duration: Option<Duration>,
+}
+
+pub struct BudgetGuard {
+    exceeded: watch::Receiver<bool>,
+    handle: JoinHandle<()>,
+}
+
+impl BudgetGuard {
+    pub async fn enforce(page: &Page, budget: JobBudget) -> Result<Self, BrowserError> {
+        let mut requests = page.event_listener::<EventRequestWillBeSent>().await?;
+        let mut received = page.event_listener::<EventDataReceived>().await?;
+        let (tx, exceeded) = watch::channel(false);
+        let page = page.clone();
+        let handle = tokio::task::spawn(async move {
+            let deadline = budget.max_duration
+                .map(|d| Instant::now() + d);
+            let mut request_count = 0;
+            let mut bytes = 0.0;
+            loop {
+                let remaining = deadline
+                    .map(|d| d.saturating_duration_since(Instant::now()))
+                    .unwrap_or(Duration::MAX);
+                tokio::select! {
+                    Some(_) = requests.next() => request_count += 1,
+                    Some(event) = received.next() => bytes += event.encoded_data_length as f64,
+                    _ = sleep(remaining) => break,
+                    else => return
+                }
+                if budget.max_requests.map(|m| request_count > m).unwrap_or(false)
+                    || budget.max_bytes.map(|m| bytes > m).unwrap_or(false) {
+                    break;
+                }
+            }
+            let _ = tx.send(true);
+            let _ = page.execute(SetBlockedUrLsParams::new(vec!["*".into()])).await;
+            let _ = page.execute(StopLoadingParams::default()).await;
+        });
+
+        Ok (
+            Self {
+                exceeded,
+                handle
+            }
+        )
+    }
+
+    pub fn check(&self) -> Result<(), BrowserError> {
+        match *self.exceeded.borrow() {
+            true => Err(BrowserError::BudgetExceeded),
+            false => Ok(())
+        }
+    }
+
+    pub async fn run<T, F>(&mut self, job: F) -> Result<T, BrowserError>
+    where
+        F: Future<Output = Result<T, BrowserError>>
+    {
+        self.check()?;
+        let exceeded = self.exceeded.wait_for(|v| *v);
+        tokio::select! {
+            result = job => result,
+            _ = exceeded => Err(BrowserError::BudgetExceeded)
+        }
+    }
+}
+
+impl Drop for BudgetGuard {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+impl BrowserSession {
+    pub async fn open_with_budget(&self, url: &str, budget: JobBudget) -> Result<(Page, BudgetGuard), BrowserError> {
+        let page = self.new_page().await?;
+        let mut guard = BudgetGuard::enforce(&page, budget).await?;
+        if let Err(e) = guard.run(self.open_on_page(url, &page)).await {
+            let _ = page.close().await;
+            return Err(e);
+        }
+
+        Ok((page, guard))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SecurityInfo {
+    pub protocol: String,
+    pub key_exchange: String,
+    pub cipher: String,
+    pub subject_name: String,
+    pub san_list: Vec<String>,
+    pub issuer: String,
+    pub valid_from: f64,
+    pub valid_to: f64,
+}
+
+impl SecurityInfo {
+    pub fn expires_in_days(&self) -> f64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or_default();
+        (self.valid_to - now) / 86400.0
+    }
+}
+
+impl From<&SecurityDetails> for SecurityInfo {
+    fn from(details: &SecurityDetails) -> Self {
+        Self {
+            protocol: details.protocol.clone(),
+            key_exchange: details.key_exchange.clone(),
+            cipher: details.cipher.clone(),
+            subject_name: details.subject_name.clone(),
+            san_list: details.san_list.clone(),
+            issuer: details.issuer.clone(),
+            valid_from: *details.valid_from.inner(),
+            valid_to: *details.valid_to.inner()
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MainResponse {
+    pub url: String,
+    pub status: i64,
+    pub headers: serde_json::Value,
+    pub mime_type: String,
+    pub protocol: Option<String>,
+    pub remote_ip_address: Option<String>,
+    pub security_state: String,
+    pub security: Option<SecurityInfo>,
+}
+
+impl From<&Response> for MainResponse {
+    fn from(response: &Response) -> Self {
+        Self {
+            url: response.url.clone(),
+            status: response.status,
+            headers: response.headers.inner().clone(),
+            mime_type: response.mime_type.clone(),
+            protocol: response.protocol.clone(),
+            remote_ip_address: response.remote_ip_address.clone(),
+            security_state: response.security_state.as_ref().to_owned(),
+            security: response.security_details
+                .as_ref()
+                .map(SecurityInfo::from)
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Asset {
+    pub url: String,
+    pub mime_type: Option<String>,
+    pub size: f64,
+    pub status: Option<i64>,
+}
+
+impl From<&NetworkEntry> for Asset {
+    fn from(entry: &NetworkEntry) -> Self {
+        Self {
+            url: entry.url.clone(),
+            mime_type: entry.mime_type.clone(),
+            size: entry.encoded_size,
+            status: entry.status
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Assets {
+    pub favicons: Vec<Asset>,
+    pub images: Vec<Asset>,
+    pub scripts: Vec<Asset>,
+    pub stylesheets: Vec<Asset>,
+    pub fonts: Vec<Asset>,
+    pub media: Vec<Asset>,
+}
+
+impl Assets {
+    pub fn from_entries(entries: &[NetworkEntry], favicon_urls: &[String]) -> Self {
+        let mut assets = Self::default();
+        for entry in entries {
+            if favicon_urls.contains(&entry.url) {
+                assets.favicons.push(entry.into());
+                continue;
+            }
+            let bucket = match entry.resource_type {
+                Some(ResourceType::Image) => &mut assets.images,
+                Some(ResourceType::Script) => &mut assets.scripts,
+                Some(ResourceType::Stylesheet) => &mut assets.stylesheets,
+                Some(ResourceType::Font) => &mut assets.fonts,
+                Some(ResourceType::Media) => &mut assets.media,
+                _ => continue
+            };
+            bucket.push(entry.into());
+        }
+        assets
+    }
+
+    pub fn total_size(&self) -> f64 {
+        [
+            &self.favicons,
+            &self.images,
+            &self.scripts,
+            &self.stylesheets,
+            &self.fonts,
+            &self.media
+        ].iter()
+            .flat_map(|v| v.iter())
+            .map(|a| a.size)
+            .sum()
+    }
+}
+
+const FAVICONS_JS: &str = r##"
+Array.from(document.querySelectorAll('link[rel~="icon"], link[rel="apple-touch-icon"]'))
+    .map(el => el.href)
+    .concat([new URL('/favicon.ico', location.href).href])
+"##;
+
+const RESOURCE_ENTRIES_JS: &str = r##"
+performance.getEntriesByType('resource').map(e => ({
+    url: e.name,
+    initiator: e.initiatorType,
+    size: e.transferSize || e.encodedBodySize || 0,
+    status: e.responseStatus || null
+}))
+"##;
+
+#[derive(Deserialize)]
+struct ResourceTimingEntry {
+    url: String,
+    initiator: String,
+    size: f64,
+    status: Option<i64>,
+}
+
+fn resource_type_of(url: &str, initiator: &str) -> Option<ResourceType> {
+    let path = url.split(['?', '#']).next().unwrap_or_default().to_lowercase();
+    let ext = path.rsplit_once('.').map(|(_, ext)| ext).unwrap_or_default();
+    match (initiator, ext) {
+        (_, "css") => Some(ResourceType::Stylesheet),
+        (_, "js" | "mjs") | ("script", _) => Some(ResourceType::Script),
+        (_, "woff" | "woff2" | "ttf" | "otf" | "eot") => Some(ResourceType::Font),
+        (_, "png" | "jpg" | "jpeg" | "gif" | "webp" | "avif" | "svg" | "ico") | ("img" | "image", _) => Some(ResourceType::Image),
+        (_, "mp4" | "webm" | "mp3" | "ogg" | "wav" | "m4a") | ("video" | "audio", _) => Some(ResourceType::Media),
+        _ => None
+    }
+}
+
+impl From<ResourceTimingEntry> for NetworkEntry {
+    fn from(timing: ResourceTimingEntry) -> Self {
+        Self {
+            resource_type: resource_type_of(&timing.url, &timing.initiator),
+            url: timing.url,
+            encoded_size: timing.size,
+            status: timing.status,
+            ..NetworkEntry::new("")
+        }
+    }
+}
+
+fn frame_resources(tree: FrameResourceTree, resources: &mut HashMap<String, (ResourceType, String)>) {
+    for resource in tree.resources {
+        resources.insert(resource.url, (resource.r#type, resource.mime_type));
+    }
+    for child in tree.child_frames.unwrap_or_default() {
+        frame_resources(child, resources);
+    }
+}
+
+fn fill_from_resources(entries: &mut [NetworkEntry], resources: &HashMap<String, (ResourceType, String)>) {
+    for entry in entries {
+        if let Some((resource_type, mime_type)) = resources.get(&entry.url) {
+            entry.resource_type = Some(resource_type.clone());
+            entry.mime_type = Some(mime_type.clone()).filter(|m| !m.is_empty());
+        }
+    }
+}
+
+async fn page_favicons(page: &Page) -> Result<Vec<String>, BrowserError> {
+    Ok (
+        page.evaluate(FAVICONS_JS).await?
+            .into_value()
+            .unwrap_or_default()
+    )
+}
+
+pub async fn collect_page_assets(page: &Page) -> Result<Assets, BrowserError> {
+    let timings: Vec<ResourceTimingEntry> = page.evaluate(RESOURCE_ENTRIES_JS).await?
+        .into_value()
+        .unwrap_or_default();
+    let mut entries: Vec<NetworkEntry> = timings.into_iter().map(NetworkEntry::from).collect();
+    // resource timing carries no MIME types, the frame resource tree does
+    let tree = page.execute(GetResourceTreeParams::default()).await?.result.frame_tree;
+    let mut resources = HashMap::new();
+    frame_resources(tree, &mut resources);
+    fill_from_resources(&mut entries, &resources);
+    let favicon_urls = page_favicons(page).await?;
+
+    Ok(Assets::from_entries(&entries, &favicon_urls))
+}
+
+pub async fn collect_page_assets_reloaded(page: &Page) -> Result<Assets, BrowserError> {
+    let recorder = NetworkRecorder::start(page).await?;
+    page.reload().await?;
+    let entries = recorder.drain(Duration::from_millis(500), Duration::from_secs(10)).await;
+    let favicon_urls = page_favicons(page).await?;
+
+    Ok(Assets::from_entries(&entries, &favicon_urls))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resource_tree_fills_mime_types() {
+        let mut entries: Vec<NetworkEntry> = [
+            ("https://example.com/app", "script"),
+            ("https://example.com/logo.png", "img"),
+            ("https://cdn.example.com/late.css", "link")
+        ].into_iter()
+            .map(|(url, initiator)| ResourceTimingEntry {
+                url: url.into(),
+                initiator: initiator.into(),
+                size: 10.0,
+                status: Some(200)
+            }.into())
+            .collect();
+        assert!(entries.iter().all(|e| e.mime_type.is_none()));
+        let resources = HashMap::from([
+            ("https://example.com/app".to_string(), (ResourceType::Script, "text/javascript".to_string())),
+            ("https://example.com/logo.png".to_string(), (ResourceType::Image, "image/png".to_string()))
+        ]);
+        fill_from_resources(&mut entries, &resources);
+        let assets = Assets::from_entries(&entries, &[]);
+        assert_eq!(assets.scripts[0].mime_type.as_deref(), Some("text/javascript"));
+        assert_eq!(assets.images[0].mime_type.as_deref(), Some("image/png"));
+        assert_eq!(assets.stylesheets[0].mime_type, None);
+        assert_eq!(assets.total_size(), 30.0);
+    }
+}
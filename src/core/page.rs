@@ -0,0 +1,223 @@
+use std::{
+    future::Future,
+    ops::Deref,
+    time::Duration
+};
+use rand::Rng;
+use tokio::{
+    runtime::Handle,
+    time::sleep
+};
+use chromiumoxide::{
+    cdp::browser_protocol::page::ReloadParams,
+    layout::Point,
+    Page
+};
+
+use crate::error::BrowserError;
+use super::browser::{BrowserSession, BrowserTimings, Wait};
+use super::fingerprint::FingerprintProfile;
+use super::hooks::isolate;
+use super::intercept::Interceptor;
+use super::network::{NetworkEntry, NetworkRecorder};
+use super::waterfall::Waterfall;
+
+
+pub struct BridgePage {
+    page: Page,
+    timings: BrowserTimings,
+    fingerprint: Option<FingerprintProfile>,
+    recorder: Option<NetworkRecorder>,
+    interceptor: Option<Interceptor>,
+}
+
+impl BridgePage {
+    pub fn new(page: Page, timings: BrowserTimings) -> Self {
+        Self {
+            page,
+            timings,
+            fingerprint: None,
+            recorder: None,
+            interceptor: None
+        }
+    }
+
+    pub fn with_fingerprint(mut self, fingerprint: Option<FingerprintProfile>) -> Self {
+        self.fingerprint = fingerprint;
+        self
+    }
+
+    pub fn with_recorder(mut self, recorder: NetworkRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    pub fn with_interceptor(mut self, interceptor: Interceptor) -> Self {
+        self.interceptor = Some(interceptor);
+        self
+    }
+
+    pub fn timings(&self) -> &BrowserTimings {
+        &self.timings
+    }
+
+    pub fn fingerprint(&self) -> Option<&FingerprintProfile> {
+        self.fingerprint.as_ref()
+    }
+
+    pub fn network_entries(&self) -> Vec<NetworkEntry> {
+        self.recorder.as_ref()
+            .map(|r| r.entries())
+            .unwrap_or_default()
+    }
+
+    pub fn waterfall(&self) -> Waterfall {
+        Waterfall::from_entries(&self.network_entries())
+    }
+
+    pub fn into_inner(self) -> Page {
+        self.page
+    }
+
+    pub async fn html(&self) -> Result<String, BrowserError> {
+        Ok(self.page.content().await?)
+    }
+
+    pub async fn wait_selector(&self, selector: &str) -> Result<(), BrowserError> {
+        self.page.wait_for_el_with_timeout(
+            selector, self.timings.page_goto_timeout
+        ).await
+    }
+
+    pub async fn click_human(&self, selector: &str) -> Result<(), BrowserError> {
+        let element = self.page.find_element(selector).await?;
+        element.scroll_into_view().await?;
+        let target = element.clickable_point().await?;
+        let steps = rand::thread_rng().gen_range(4..9);
+        let start = Point {
+            x: target.x - rand::thread_rng().gen_range(40.0..160.0),
+            y: target.y - rand::thread_rng().gen_range(20.0..80.0)
+        };
+        for step in 1..=steps {
+            let progress = step as f64 / steps as f64;
+            let (jitter_x, jitter_y) = {
+                let mut rng = rand::thread_rng();
+                (rng.gen_range(-2.0..2.0), rng.gen_range(-2.0..2.0))
+            };
+            self.page.move_mouse(Point {
+                x: start.x + (target.x - start.x) * progress + jitter_x * (1.0 - progress),
+                y: start.y + (target.y - start.y) * progress + jitter_y * (1.0 - progress)
+            }).await?;
+            sleep(
+                Duration::from_millis(rand::thread_rng().gen_range(8..24))
+            ).await;
+        }
+        sleep(
+            Duration::from_millis(self.timings.action_sleep)
+        ).await;
+        self.page.click(target).await?;
+
+        Ok(())
+    }
+
+    pub async fn close(self) -> Result<(), BrowserError> {
+        drop(self.interceptor);
+        drop(self.recorder);
+        Ok(self.page.close().await?)
+    }
+}
+
+impl Deref for BridgePage {
+    type Target = Page;
+
+    fn deref(&self) -> &Page {
+        &self.page
+    }
+}
+
+pub async fn hard_reload(page: &Page) -> Result<(), BrowserError> {
+    page.execute(
+        ReloadParams::builder()
+            .ignore_cache(true)
+            .build()
+    ).await?;
+    page.wait_for_navigation().await?;
+    Ok(())
+}
+
+pub struct PageGuard {
+    page: Option<Page>,
+}
+
+impl PageGuard {
+    pub fn new(page: Page) -> Self {
+        Self { page: Some(page) }
+    }
+
+    pub fn into_inner(mut self) -> Page {
+        self.page.take().expect("page guard already released")
+    }
+
+    pub async fn close(mut self) -> Result<(), BrowserError> {
+        match self.page.take() {
+            Some(page) => Ok(page.close().await?),
+            None => Ok(())
+        }
+    }
+}
+
+impl Deref for PageGuard {
+    type Target = Page;
+
+    fn deref(&self) -> &Page {
+        self.page.as_ref().expect("page guard already released")
+    }
+}
+
+impl Drop for PageGuard {
+    fn drop(&mut self) {
+        let Some(page) = self.page.take() else {
+            return;
+        };
+        let Ok(runtime) = Handle::try_current() else {
+            return;
+        };
+        runtime.spawn(async move {
+            let _ = page.close().await;
+        });
+    }
+}
+
+impl BrowserSession {
+    pub async fn open_scoped(&self, url: &str) -> Result<PageGuard, BrowserError> {
+        let page = PageGuard::new(self.new_page().await?);
+        self.open_on_page(url, &page).await?;
+
+        Ok(page)
+    }
+
+    pub async fn with_open<T, F, Fut>(&self, url: &str, callback: F) -> Result<T, BrowserError>
+    where
+        T: Send + 'static,
+        F: FnOnce(Page) -> Fut,
+        Fut: Future<Output = Result<T, BrowserError>> + Send + 'static
+    {
+        let page = self.open_scoped(url).await?;
+        let result = isolate(callback(page.clone())).await;
+        let _ = page.close().await;
+        result
+    }
+
+    pub fn bridge(&self, page: Page) -> BridgePage {
+        BridgePage::new(page, self.timings.clone())
+            .with_fingerprint(self.fingerprint.clone())
+    }
+
+    pub async fn open_bridge(&self, url: &str) -> Result<BridgePage, BrowserError> {
+        let page = self.new_page().await?;
+        let recorder = NetworkRecorder::start(&page).await?;
+        self.open_on_page(url, &page).await?;
+
+        Ok(self.bridge(page).with_recorder(recorder))
+    }
+}
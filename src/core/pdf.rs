@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use chromiumoxide::{
+    cdp::browser_protocol::page::PrintToPdfParams,
+    Page
+};
+
+use crate::error::BrowserError;
+
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FormFillReport {
+    pub filled: Vec<String>,
+    pub missing: Vec<String>,
+}
+
+const FILL_FORM_JS: &str = r##"
+(() => {
+    const values = __VALUES__;
+    const filled = [];
+    const missing = [];
+    const find = (key) => {
+        let found = [];
+        try { found = Array.from(document.querySelectorAll(key)); } catch (e) {}
+        if (!found.length) {
+            found = Array.from(document.getElementsByName(key));
+        }
+        if (!found.length) {
+            const byId = document.getElementById(key);
+            if (byId) found = [byId];
+        }
+        return found;
+    };
+    const fire = (el) => {
+        el.dispatchEvent(new Event('input', { bubbles: true }));
+        el.dispatchEvent(new Event('change', { bubbles: true }));
+    };
+    for (const [key, value] of Object.entries(values)) {
+        const elements = find(key);
+        if (!elements.length) {
+            missing.push(key);
+            continue;
+        }
+        for (const el of elements) {
+            const type = (el.type || '').toLowerCase();
+            if (type === 'checkbox') {
+                el.checked = Array.isArray(value) ? value.includes(el.value) : !!value;
+            } else if (type === 'radio') {
+                el.checked = el.value === String(value);
+            } else if (el.tagName === 'SELECT') {
+                const wanted = Array.isArray(value) ? value.map(String) : [String(value)];
+                for (const option of el.options) {
+                    option.selected = wanted.includes(option.value) || wanted.includes(option.text);
+                }
+            } else if (el.isContentEditable) {
+                el.textContent = String(value);
+            } else {
+                el.value = String(value);
+            }
+            fire(el);
+        }
+        filled.push(key);
+    }
+    return { filled, missing };
+})()
+"##;
+
+const FLATTEN_FORM_JS: &str = r##"
+(() => {
+    let flattened = 0;
+    const replace = (el, text) => {
+        const span = document.createElement('span');
+        span.textContent = text;
+        span.className = 'bb-flattened';
+        span.style.whiteSpace = 'pre-wrap';
+        el.replaceWith(span);
+        flattened++;
+    };
+    for (const el of Array.from(document.querySelectorAll('input, textarea, select'))) {
+        const type = (el.type || '').toLowerCase();
+        if (['hidden', 'submit', 'button', 'reset', 'image', 'file'].includes(type)) {
+            continue;
+        }
+        if (type === 'checkbox' || type === 'radio') {
+            replace(el, el.checked ? '☒' : '☐');
+        } else if (el.tagName === 'SELECT') {
+            replace(el, Array.from(el.selectedOptions).map(o => o.text).join(', '));
+        } else {
+            replace(el, el.value);
+        }
+    }
+    for (const el of Array.from(document.querySelectorAll('button, input[type=submit], input[type=button], input[type=reset]'))) {
+        el.remove();
+    }
+    return flattened;
+})()
+"##;
+
+pub async fn fill_form(page: &Page, values: &BTreeMap<String, Value>) -> Result<FormFillReport, BrowserError> {
+    let values = serde_json::to_string(values)
+        .map_err(|_| BrowserError::Serialization)?;
+    page.evaluate(FILL_FORM_JS.replace("__VALUES__", &values)).await?
+        .into_value()
+        .map_err(|_| BrowserError::Serialization)
+}
+
+pub async fn flatten_form(page: &Page) -> Result<usize, BrowserError> {
+    page.evaluate(FLATTEN_FORM_JS).await?
+        .into_value()
+        .map_err(|_| BrowserError::Serialization)
+}
+
+pub async fn print_pdf(page: &Page, params: PrintToPdfParams) -> Result<Vec<u8>, BrowserError> {
+    Ok(page.pdf(params).await?)
+}
+
+pub async fn fill_form_to_pdf(
+    page: &Page, values: &BTreeMap<String, Value>, flatten: bool
+) -> Result<(FormFillReport, Vec<u8>), BrowserError> {
+    let report = fill_form(page, values).await?;
+    if flatten {
+        flatten_form(page).await?;
+    }
+    let params = PrintToPdfParams {
+        print_background: Some(true),
+        ..Default::default()
+    };
+    let pdf = print_pdf(page, params).await?;
+
+    Ok((report, pdf))
+}
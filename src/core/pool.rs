@@ -0,0 +1,467 @@
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    ops::Deref,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH}
+};
+use tokio::{
+    runtime::Handle,
+    sync::Mutex,
+    task::JoinHandle,
+    time::{sleep, timeout}
+};
+use serde::{Deserialize, Serialize};
+use chromiumoxide::{
+    cdp::browser_protocol::{
+        browser::BrowserContextId,
+        storage::{ClearCookiesParams, ClearDataForOriginParams},
+        target::TargetId
+    },
+    Page
+};
+
+use crate::error::BrowserError;
+use super::browser::{BrowserSession, BrowserSessionConfig};
+use super::gc::{TabGc, TabGcOptions};
+use super::hooks::isolate;
+
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SiteConfig {
+    pub origin: String,
+    pub homepage: Option<String>,
+    pub warm_pages: usize,
+}
+
+impl SiteConfig {
+    pub fn new(origin: &str) -> Self {
+        Self {
+            origin: origin.trim_end_matches('/').to_owned(),
+            homepage: None,
+            warm_pages: 1
+        }
+    }
+
+    fn homepage(&self) -> String {
+        self.homepage.clone()
+            .unwrap_or_else(|| format!("{}/", self.origin))
+    }
+}
+
+fn url_origin(url: &str) -> Option<String> {
+    let end = url.match_indices('/').nth(2)
+        .map(|(i, _)| i)
+        .unwrap_or(url.len());
+    url.contains("://").then(|| url[..end].to_owned())
+}
+
+#[derive(Clone, Debug)]
+pub struct BrowserPoolConfig {
+    pub size: usize,
+    pub session: BrowserSessionConfig,
+    pub max_idle_pages: usize,
+    pub isolate_pages: bool,
+    pub health_check_interval: u64,
+    pub health_check_timeout: u64,
+    pub sites: Vec<SiteConfig>,
+    pub tab_gc: Option<TabGcOptions>,
+}
+
+impl Default for BrowserPoolConfig {
+    fn default() -> Self {
+        Self {
+            size: 2,
+            session: BrowserSessionConfig::default(),
+            max_idle_pages: 4,
+            isolate_pages: false,
+            health_check_interval: 5000,
+            health_check_timeout: 2000,
+            sites: Vec::new(),
+            tab_gc: Some(TabGcOptions::default())
+        }
+    }
+}
+
+struct PoolSlot {
+    session: BrowserSession,
+    user_data_dir: PathBuf,
+    idle_pages: Vec<Page>,
+    warm_pages: HashMap<String, Vec<Page>>,
+    leased: HashSet<TargetId>,
+    contexts: HashMap<TargetId, BrowserContextId>,
+    tab_gc: Option<TabGc>,
+    generation: u64,
+}
+
+fn temp_user_data_dir(idx: usize) -> Result<PathBuf, BrowserError> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let dir = std::env::temp_dir().join(
+        format!("browser_bridge_{}_{idx}_{nanos}", std::process::id())
+    );
+    std::fs::create_dir_all(&dir)
+        .map_err(|_| BrowserError::BrowserLaunch)?;
+    Ok(dir)
+}
+
+async fn launch_slot(config: &BrowserPoolConfig, idx: usize, generation: u64) -> Result<PoolSlot, BrowserError> {
+    let user_data_dir = temp_user_data_dir(idx)?;
+    let tab_gc = config.tab_gc.clone().map(TabGc::new);
+    let mut config = config.session.clone();
+    config.port = 0;
+    config.user_data_dir = Some(user_data_dir.to_string_lossy().into_owned());
+    let session = BrowserSession::launch(config).await?;
+
+    Ok (
+        PoolSlot {
+            session,
+            user_data_dir,
+            idle_pages: Vec::new(),
+            warm_pages: HashMap::new(),
+            leased: HashSet::new(),
+            contexts: HashMap::new(),
+            tab_gc,
+            generation
+        }
+    )
+}
+
+async fn prewarm_slot(slot: &mut PoolSlot, sites: &[SiteConfig]) -> Result<(), BrowserError> {
+    for site in sites {
+        let warm = slot.warm_pages.entry(site.origin.clone()).or_default().len();
+        for _ in warm..site.warm_pages {
+            let page = slot.new_page().await?;
+            slot.session.open_on_page(&site.homepage(), &page).await?;
+            slot.warm_pages.entry(site.origin.clone()).or_default().push(page);
+        }
+    }
+    Ok(())
+}
+
+impl PoolSlot {
+    async fn new_page(&mut self) -> Result<Page, BrowserError> {
+        let (page, context_id) = self.session.new_isolated_page().await?;
+        self.contexts.insert(page.target_id().clone(), context_id);
+        self.track(&page);
+        Ok(page)
+    }
+
+    async fn dispose_page(&mut self, page: Page) {
+        self.release(&page);
+        match self.contexts.remove(page.target_id()) {
+            Some(context_id) => {
+                let _ = self.session.browser.dispose_browser_context(context_id).await;
+            },
+            None => {
+                let _ = page.close().await;
+            }
+        }
+    }
+
+    async fn reset_page(&self, page: &Page) -> Result<(), BrowserError> {
+        let origin = page.url().await?
+            .as_deref()
+            .and_then(url_origin);
+        page.goto("about:blank").await?;
+        if let Some(origin) = origin {
+            page.execute(ClearDataForOriginParams::new(origin, "all")).await?;
+        }
+        self.session.browser.execute(ClearCookiesParams {
+            browser_context_id: self.contexts.get(page.target_id()).cloned()
+        }).await?;
+        Ok(())
+    }
+
+    fn track(&self, page: &Page) {
+        if let Some(tab_gc) = &self.tab_gc {
+            tab_gc.track(page);
+        }
+    }
+
+    fn release(&mut self, page: &Page) {
+        self.leased.remove(page.target_id());
+        if let Some(tab_gc) = &self.tab_gc {
+            tab_gc.release(page);
+        }
+    }
+}
+
+async fn shutdown_slot(slot: &mut PoolSlot) {
+    slot.idle_pages.clear();
+    slot.warm_pages.clear();
+    slot.contexts.clear();
+    slot.session.close().await;
+    let _ = std::fs::remove_dir_all(&slot.user_data_dir);
+}
+
+async fn check_slot(slot: &Arc<Mutex<PoolSlot>>, config: &BrowserPoolConfig, idx: usize) -> Result<(), BrowserError> {
+    let mut slot = slot.lock().await;
+    let alive = timeout(
+        Duration::from_millis(config.health_check_timeout),
+        slot.session.browser.version()
+    ).await;
+    if matches!(alive, Ok(Ok(_))) {
+        return Ok(());
+    }
+    let generation = slot.generation + 1;
+    shutdown_slot(&mut slot).await;
+    *slot = launch_slot(config, idx, generation).await?;
+    prewarm_slot(&mut slot, &config.sites).await
+}
+
+async fn collect_slot(slot: &Arc<Mutex<PoolSlot>>) -> Result<usize, BrowserError> {
+    let slot = slot.lock().await;
+    let Some(tab_gc) = slot.tab_gc.as_ref() else {
+        return Ok(0);
+    };
+    let keep: Vec<TargetId> = slot.idle_pages.iter()
+        .chain(slot.warm_pages.values().flatten())
+        .map(|p| p.target_id().clone())
+        .chain(slot.leased.iter().cloned())
+        .collect();
+    Ok(tab_gc.collect(&slot.session, &keep).await?.len())
+}
+
+pub struct BrowserPool {
+    config: BrowserPoolConfig,
+    slots: Arc<Vec<Arc<Mutex<PoolSlot>>>>,
+    cursor: AtomicUsize,
+    health_handle: JoinHandle<()>,
+    closed: bool,
+}
+
+impl BrowserPool {
+    pub async fn launch(config: BrowserPoolConfig) -> Result<Self, BrowserError> {
+        let mut slots = Vec::with_capacity(config.size);
+        for idx in 0..config.size.max(1) {
+            slots.push(Arc::new(Mutex::new(
+                launch_slot(&config, idx, 0).await?
+            )));
+        }
+        for slot in slots.iter() {
+            prewarm_slot(&mut *slot.lock().await, &config.sites).await?;
+        }
+        let slots = Arc::new(slots);
+        let health_slots = slots.clone();
+        let health_config = config.clone();
+        let health_handle = tokio::task::spawn(async move {
+            loop {
+                sleep(
+                    Duration::from_millis(health_config.health_check_interval)
+                ).await;
+                for (idx, slot) in health_slots.iter().enumerate() {
+                    let _ = check_slot(slot, &health_config, idx).await;
+                    let _ = collect_slot(slot).await;
+                }
+            }
+        });
+
+        Ok (
+            Self {
+                config,
+                slots,
+                cursor: AtomicUsize::new(0),
+                health_handle,
+                closed: false
+            }
+        )
+    }
+
+    pub fn size(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub async fn health_check(&self) -> Result<(), BrowserError> {
+        for (idx, slot) in self.slots.iter().enumerate() {
+            check_slot(slot, &self.config, idx).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn collect_zombie_tabs(&self) -> Result<usize, BrowserError> {
+        let mut closed = 0;
+        for slot in self.slots.iter() {
+            closed += collect_slot(slot).await?;
+        }
+        Ok(closed)
+    }
+
+    pub async fn checkout_page(&self) -> Result<PooledPage, BrowserError> {
+        let idx = self.cursor.fetch_add(1, Ordering::Relaxed) % self.slots.len();
+        let slot = self.slots[idx].clone();
+        let (page, generation) = {
+            let mut guard = slot.lock().await;
+            let page = match guard.idle_pages.pop() {
+                Some(page) => page,
+                None => guard.new_page().await?
+            };
+            guard.track(&page);
+            guard.leased.insert(page.target_id().clone());
+            (page, guard.generation)
+        };
+
+        Ok (
+            PooledPage {
+                page: Some(page),
+                slot,
+                generation,
+                max_idle_pages: self.config.max_idle_pages,
+                isolate_pages: self.config.isolate_pages
+            }
+        )
+    }
+
+    pub async fn prewarm(&self) -> Result<(), BrowserError> {
+        for slot in self.slots.iter() {
+            prewarm_slot(&mut *slot.lock().await, &self.config.sites).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn checkout_page_for(&self, url: &str) -> Result<PooledPage, BrowserError> {
+        let Some(origin) = url_origin(url) else {
+            return self.checkout_page().await;
+        };
+        for slot in self.slots.iter() {
+            let mut guard = slot.lock().await;
+            let Some(page) = guard.warm_pages.get_mut(&origin).and_then(|p| p.pop()) else {
+                continue;
+            };
+            guard.track(&page);
+            guard.leased.insert(page.target_id().clone());
+            let generation = guard.generation;
+            let refill_slot = slot.clone();
+            let sites: Vec<SiteConfig> = self.config.sites.iter()
+                .filter(|s| s.origin == origin)
+                .cloned()
+                .collect();
+            tokio::task::spawn(async move {
+                let _ = prewarm_slot(&mut *refill_slot.lock().await, &sites).await;
+            });
+
+            return Ok (
+                PooledPage {
+                    page: Some(page),
+                    slot: slot.clone(),
+                    generation,
+                    max_idle_pages: self.config.max_idle_pages,
+                    isolate_pages: self.config.isolate_pages
+                }
+            );
+        }
+        self.checkout_page().await
+    }
+
+    pub async fn with_page<T, F, Fut>(&self, url: &str, callback: F) -> Result<T, BrowserError>
+    where
+        T: Send + 'static,
+        F: FnOnce(Page) -> Fut,
+        Fut: Future<Output = Result<T, BrowserError>> + Send + 'static
+    {
+        let page = self.checkout_page_for(url).await?;
+        page.goto(url).await?;
+        let result = isolate(callback((*page).clone())).await;
+        if matches!(result, Err(BrowserError::CallbackPanicked(_))) {
+            page.discard().await;
+        }
+        result
+    }
+
+    pub async fn close(mut self) {
+        self.health_handle.abort();
+        for slot in self.slots.iter() {
+            shutdown_slot(&mut *slot.lock().await).await;
+        }
+        self.closed = true;
+    }
+}
+
+impl Drop for BrowserPool {
+    fn drop(&mut self) {
+        self.health_handle.abort();
+        if self.closed {
+            return;
+        }
+        let Ok(runtime) = Handle::try_current() else {
+            return;
+        };
+        let slots = self.slots.clone();
+        runtime.spawn(async move {
+            for slot in slots.iter() {
+                shutdown_slot(&mut *slot.lock().await).await;
+            }
+        });
+    }
+}
+
+pub struct PooledPage {
+    page: Option<Page>,
+    slot: Arc<Mutex<PoolSlot>>,
+    generation: u64,
+    max_idle_pages: usize,
+    isolate_pages: bool,
+}
+
+impl PooledPage {
+    pub async fn discard(mut self) {
+        if let Some(page) = self.page.take() {
+            self.slot.lock().await.dispose_page(page).await;
+        }
+    }
+}
+
+impl Deref for PooledPage {
+    type Target = Page;
+
+    fn deref(&self) -> &Page {
+        self.page.as_ref().expect("pooled page already released")
+    }
+}
+
+impl Drop for PooledPage {
+    fn drop(&mut self) {
+        let Some(page) = self.page.take() else {
+            return;
+        };
+        let slot = self.slot.clone();
+        let generation = self.generation;
+        let max_idle_pages = self.max_idle_pages;
+        let isolate_pages = self.isolate_pages;
+        let Ok(runtime) = Handle::try_current() else {
+            return;
+        };
+        runtime.spawn(async move {
+            let mut slot = slot.lock().await;
+            if slot.generation != generation {
+                drop(slot);
+                let _ = page.close().await;
+                return;
+            }
+            if slot.idle_pages.len() >= max_idle_pages {
+                slot.dispose_page(page).await;
+                return;
+            }
+            if isolate_pages {
+                slot.dispose_page(page).await;
+                if let Ok(fresh) = slot.new_page().await {
+                    slot.idle_pages.push(fresh);
+                }
+                return;
+            }
+            match slot.reset_page(&page).await {
+                Ok(()) => {
+                    slot.release(&page);
+                    slot.idle_pages.push(page);
+                },
+                Err(_) => slot.dispose_page(page).await
+            }
+        });
+    }
+}
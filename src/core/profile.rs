@@ -0,0 +1,130 @@
+use std::{
+    fs,
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime}
+};
+use serde::{Deserialize, Serialize};
+
+
+static CACHE_DIRS: [&str; 3] = [
+    "Cache",
+    "Code Cache",
+    "Media Cache"
+];
+
+static GPU_CACHE_DIRS: [&str; 7] = [
+    "GPUCache",
+    "ShaderCache",
+    "GrShaderCache",
+    "GraphiteDawnCache",
+    "DawnCache",
+    "DawnGraphiteCache",
+    "DawnWebGPUCache"
+];
+
+static SERVICE_WORKER_DIRS: [&str; 2] = [
+    "Service Worker/CacheStorage",
+    "Service Worker/ScriptCache"
+];
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PruneOptions {
+    pub caches: bool,
+    pub gpu_caches: bool,
+    pub service_workers: bool,
+    pub service_worker_max_age: Option<Duration>,
+}
+
+impl Default for PruneOptions {
+    fn default() -> Self {
+        Self {
+            caches: true,
+            gpu_caches: true,
+            service_workers: true,
+            service_worker_max_age: Some(Duration::from_secs(7 * 24 * 3600))
+        }
+    }
+}
+
+pub fn profile_size(user_data_dir: impl AsRef<Path>) -> io::Result<u64> {
+    let metadata = fs::symlink_metadata(user_data_dir.as_ref())?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+    let mut size = 0;
+    for entry in fs::read_dir(user_data_dir)? {
+        size += profile_size(entry?.path()).unwrap_or(0);
+    }
+    Ok(size)
+}
+
+fn remove_path(path: &Path) -> io::Result<u64> {
+    let size = profile_size(path)?;
+    match path.is_dir() {
+        true => fs::remove_dir_all(path)?,
+        false => fs::remove_file(path)?
+    }
+    Ok(size)
+}
+
+fn is_older_than(path: &Path, max_age: Duration) -> bool {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| SystemTime::now().duration_since(t).ok())
+        .map(|age| age > max_age)
+        .unwrap_or(false)
+}
+
+fn profile_dirs(user_data_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut dirs = vec![user_data_dir.to_path_buf()];
+    for entry in fs::read_dir(user_data_dir)? {
+        let path = entry?.path();
+        let is_profile = path.file_name()
+            .and_then(|n| n.to_str())
+            .map(|n| n == "Default" || n.starts_with("Profile "))
+            .unwrap_or(false);
+        if is_profile && path.is_dir() {
+            dirs.push(path);
+        }
+    }
+    Ok(dirs)
+}
+
+pub fn prune_profile(user_data_dir: impl AsRef<Path>, options: &PruneOptions) -> io::Result<u64> {
+    let mut freed = 0;
+    for dir in profile_dirs(user_data_dir.as_ref())? {
+        let mut targets: Vec<&str> = Vec::new();
+        if options.caches {
+            targets.extend_from_slice(&CACHE_DIRS);
+        }
+        if options.gpu_caches {
+            targets.extend_from_slice(&GPU_CACHE_DIRS);
+        }
+        for target in targets {
+            let path = dir.join(target);
+            if path.exists() {
+                freed += remove_path(&path).unwrap_or(0);
+            }
+        }
+        if !options.service_workers {
+            continue;
+        }
+        for target in SERVICE_WORKER_DIRS {
+            let path = dir.join(target);
+            let Ok(entries) = fs::read_dir(&path) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let stale = options.service_worker_max_age
+                    .map(|max_age| is_older_than(&entry.path(), max_age))
+                    .unwrap_or(true);
+                if stale {
+                    freed += remove_path(&entry.path()).unwrap_or(0);
+                }
+            }
+        }
+    }
+    Ok(freed)
+}
@@ -0,0 +1,405 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant}
+};
+use serde::{Deserialize, Serialize};
+
+use crate::error::BrowserError;
+use chromiumoxide::Page;
+
+use super::browser::{BrowserSession, MyIP, PageParam};
+
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProxySwitchReport {
+    pub latency: Duration,
+    pub previous_ip: MyIP,
+    pub verified_ip: MyIP,
+}
+
+#[derive(Debug)]
+pub struct IpCache {
+    ttl: Duration,
+    active_proxy: Mutex<Option<String>>,
+    entries: Mutex<HashMap<Option<String>, (MyIP, Instant)>>,
+}
+
+impl IpCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            active_proxy: Mutex::new(None),
+            entries: Mutex::new(HashMap::new())
+        }
+    }
+
+    pub fn active_proxy(&self) -> Option<String> {
+        self.active_proxy.lock().unwrap().clone()
+    }
+
+    pub(crate) fn set_active_proxy(&self, proxy: Option<&str>) {
+        *self.active_proxy.lock().unwrap() = proxy.map(|p| p.to_owned());
+    }
+
+    pub fn get(&self, proxy: &Option<String>) -> Option<MyIP> {
+        self.entries.lock().unwrap()
+            .get(proxy)
+            .filter(|(_, at)| at.elapsed() < self.ttl)
+            .map(|(ip, _)| ip.clone())
+    }
+
+    pub(crate) fn insert(&self, proxy: Option<String>, ip: MyIP) {
+        self.entries.lock().unwrap().insert(proxy, (ip, Instant::now()));
+    }
+
+    pub fn invalidate(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+impl BrowserSession {
+    pub async fn current_ip_cached(&self) -> Result<MyIP, BrowserError> {
+        let proxy = self.ip_cache.active_proxy();
+        match self.ip_cache.get(&proxy) {
+            Some(ip) => Ok(ip),
+            None => self.myip().await
+        }
+    }
+
+    pub async fn set_proxy_verified(&self, proxy: &str) -> Result<ProxySwitchReport, BrowserError> {
+        let previous_ip = self.current_ip_cached().await?;
+        let previous_proxy = self.ip_cache.active_proxy();
+        self.set_proxy(proxy).await?;
+        let start = Instant::now();
+        let verified = match self.myip().await {
+            Ok(ip) if ip.ip == previous_ip.ip => Err(BrowserError::ProxyNotApplied),
+            result => result
+        };
+        let latency = start.elapsed();
+        let verified_ip = match verified {
+            Ok(ip) => ip,
+            Err(e) => {
+                self.restore_proxy(previous_proxy.as_deref()).await?;
+                return Err(e);
+            }
+        };
+
+        Ok (
+            ProxySwitchReport {
+                latency,
+                previous_ip,
+                verified_ip
+            }
+        )
+    }
+
+    async fn restore_proxy(&self, proxy: Option<&str>) -> Result<(), BrowserError> {
+        match proxy {
+            Some(proxy) => self.set_proxy(proxy).await,
+            None => self.reset_proxy().await
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProxyEntry {
+    pub proxy: String,
+    pub ip: Option<MyIP>,
+    pub verified_at: Option<Instant>,
+    pub latency: Option<Duration>,
+    pub successes: u32,
+    pub failures: u32,
+}
+
+impl ProxyEntry {
+    pub fn success_rate(&self) -> Option<f64> {
+        let total = self.successes + self.failures;
+        (total > 0).then(|| self.successes as f64 / total as f64)
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.successes + self.failures < 3
+            || self.success_rate().unwrap_or(1.0) >= 0.5
+    }
+
+    fn decay(&mut self) {
+        if self.successes + self.failures > 20 {
+            self.successes /= 2;
+            self.failures /= 2;
+        }
+    }
+
+    pub fn record_success(&mut self, latency: Duration) {
+        self.decay();
+        self.successes += 1;
+        self.latency = Some(match self.latency {
+            Some(prev) => prev.mul_f64(0.7) + latency.mul_f64(0.3),
+            None => latency
+        });
+    }
+
+    pub fn record_failure(&mut self) {
+        self.decay();
+        self.failures += 1;
+    }
+
+    fn rank_key(&self) -> (bool, Duration) {
+        (!self.is_healthy(), self.latency.unwrap_or(Duration::MAX))
+    }
+
+    fn is_fresh(&self, ttl: Duration) -> bool {
+        self.verified_at
+            .map(|t| t.elapsed() < ttl)
+            .unwrap_or(false)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum RotationPolicy {
+    #[default]
+    RoundRobin,
+    FastestHealthy,
+}
+
+#[derive(Clone, Debug)]
+pub struct ProxyRotator {
+    pub entries: Vec<ProxyEntry>,
+    pub ttl: Duration,
+    pub policy: RotationPolicy,
+    cursor: usize,
+}
+
+impl ProxyRotator {
+    pub fn new<I, S>(proxies: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>
+    {
+        Self {
+            entries: proxies.into_iter()
+                .map(|p| ProxyEntry {
+                    proxy: p.into(),
+                    ip: None,
+                    verified_at: None,
+                    latency: None,
+                    successes: 0,
+                    failures: 0
+                })
+                .collect(),
+            ttl: Duration::from_secs(3600),
+            policy: RotationPolicy::default(),
+            cursor: 0
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn with_policy(mut self, policy: RotationPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    fn fastest_matching(&mut self, f: impl Fn(&ProxyEntry) -> bool) -> Option<&str> {
+        let idx = self.entries.iter()
+            .enumerate()
+            .filter(|(_, e)| f(e) && e.is_healthy())
+            .min_by_key(|(_, e)| e.rank_key())
+            .map(|(idx, _)| idx);
+        match idx {
+            Some(idx) => Some(&self.entries[idx].proxy),
+            None => self.round_robin_matching(f)
+        }
+    }
+
+    fn next_matching(&mut self, f: impl Fn(&ProxyEntry) -> bool) -> Option<&str> {
+        match self.policy {
+            RotationPolicy::RoundRobin => self.round_robin_matching(f),
+            RotationPolicy::FastestHealthy => self.fastest_matching(f)
+        }
+    }
+
+    fn round_robin_matching(&mut self, f: impl Fn(&ProxyEntry) -> bool) -> Option<&str> {
+        let len = self.entries.len();
+        for offset in 0..len {
+            let idx = (self.cursor + offset) % len;
+            if f(&self.entries[idx]) {
+                self.cursor = idx + 1;
+                return Some(&self.entries[idx].proxy);
+            }
+        }
+        None
+    }
+
+    pub fn next_proxy(&mut self) -> Option<&str> {
+        self.next_matching(|_| true)
+    }
+
+    pub fn next_for_country(&mut self, cc: &str) -> Option<&str> {
+        let ttl = self.ttl;
+        self.next_matching(|e| {
+            e.is_fresh(ttl) && e.ip.as_ref()
+                .map(|ip| ip.cc.eq_ignore_ascii_case(cc))
+                .unwrap_or(false)
+        })
+    }
+
+    fn entry_mut(&mut self, proxy: &str) -> Option<&mut ProxyEntry> {
+        self.entries.iter_mut().find(|e| e.proxy == proxy)
+    }
+
+    pub fn record_success(&mut self, proxy: &str, latency: Duration) {
+        if let Some(entry) = self.entry_mut(proxy) {
+            entry.record_success(latency);
+        }
+    }
+
+    pub fn record_failure(&mut self, proxy: &str) {
+        if let Some(entry) = self.entry_mut(proxy) {
+            entry.record_failure();
+        }
+    }
+
+    pub fn ranking(&self) -> Vec<&ProxyEntry> {
+        let mut ranked: Vec<&ProxyEntry> = self.entries.iter().collect();
+        ranked.sort_by_key(|e| e.rank_key());
+        ranked
+    }
+
+    pub async fn measure(&mut self, session: &BrowserSession, url: &str) {
+        let proxies: Vec<String> = self.entries.iter().map(|e| e.proxy.clone()).collect();
+        for proxy in proxies {
+            if let Ok(page) = session.open_via_proxy(url, &proxy, self).await {
+                let _ = page.close().await;
+            }
+        }
+    }
+
+    pub async fn verify(&mut self, session: &BrowserSession) -> Result<(), BrowserError> {
+        let ttl = self.ttl;
+        for entry in self.entries.iter_mut().filter(|e| !e.is_fresh(ttl)) {
+            let param = PageParam {
+                proxy: Some(&entry.proxy),
+                ..Default::default()
+            };
+            entry.ip = session.myip_with(&param).await.ok();
+            entry.verified_at = Some(Instant::now());
+        }
+        Ok(())
+    }
+}
+
+fn is_proxy_failure(status: i64) -> bool {
+    matches!(status, 407 | 502 | 504)
+}
+
+impl BrowserSession {
+    async fn open_via_proxy(&self, url: &str, proxy: &str, rotator: &mut ProxyRotator) -> Result<Page, BrowserError> {
+        let param = PageParam {
+            proxy: Some(proxy),
+            ..Default::default()
+        };
+        let page = self.new_page_with(&param).await?;
+        let start = Instant::now();
+        match self.open_page_for_response(url, page).await {
+            Ok((page, response)) if !is_proxy_failure(response.status) => {
+                rotator.record_success(proxy, start.elapsed());
+                Ok(page)
+            },
+            Ok((page, _)) => {
+                rotator.record_failure(proxy);
+                let _ = page.close().await;
+                Err(BrowserError::ProxyDead)
+            },
+            Err(e) => {
+                rotator.record_failure(proxy);
+                Err(e)
+            }
+        }
+    }
+
+    pub async fn open_rotated(&self, url: &str, rotator: &mut ProxyRotator) -> Result<Page, BrowserError> {
+        let proxy = rotator.next_proxy()
+            .ok_or(BrowserError::ProxyNotFound)?
+            .to_owned();
+        self.open_via_proxy(url, &proxy, rotator).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn failures_mark_unhealthy_until_successes_recover() {
+        let mut rotator = ProxyRotator::new(["a"]);
+        rotator.record_failure("a");
+        rotator.record_failure("a");
+        assert!(rotator.entries[0].is_healthy());
+        rotator.record_failure("a");
+        assert!(!rotator.entries[0].is_healthy());
+        for _ in 0..3 {
+            rotator.record_success("a", ms(100));
+        }
+        assert!(rotator.entries[0].is_healthy());
+        assert_eq!(rotator.entries[0].success_rate(), Some(0.5));
+    }
+
+    #[test]
+    fn counters_decay_so_old_failures_fade() {
+        let mut rotator = ProxyRotator::new(["a"]);
+        for _ in 0..21 {
+            rotator.record_failure("a");
+        }
+        rotator.record_success("a", ms(100));
+        let entry = &rotator.entries[0];
+        assert_eq!((entry.successes, entry.failures), (1, 10));
+    }
+
+    #[test]
+    fn latency_is_an_exponential_moving_average() {
+        let mut rotator = ProxyRotator::new(["a"]);
+        rotator.record_success("a", ms(100));
+        assert_eq!(rotator.entries[0].latency, Some(ms(100)));
+        rotator.record_success("a", ms(200));
+        assert_eq!(rotator.entries[0].latency, Some(ms(130)));
+    }
+
+    #[test]
+    fn ranks_healthy_fastest_first_and_keeps_ties_in_order() {
+        let mut rotator = ProxyRotator::new(["slow", "tie_a", "tie_b", "dead", "fresh"])
+            .with_policy(RotationPolicy::FastestHealthy);
+        rotator.record_success("slow", ms(300));
+        rotator.record_success("tie_a", ms(100));
+        rotator.record_success("tie_b", ms(100));
+        rotator.record_success("dead", ms(10));
+        for _ in 0..3 {
+            rotator.record_failure("dead");
+        }
+        let ranking: Vec<&str> = rotator.ranking().iter().map(|e| e.proxy.as_str()).collect();
+        assert_eq!(ranking, ["tie_a", "tie_b", "slow", "fresh", "dead"]);
+        assert_eq!(rotator.next_proxy(), Some("tie_a"));
+        assert_eq!(rotator.next_proxy(), Some("tie_a"));
+    }
+
+    #[test]
+    fn fastest_falls_back_to_round_robin_when_all_unhealthy() {
+        let mut rotator = ProxyRotator::new(["a", "b"])
+            .with_policy(RotationPolicy::FastestHealthy);
+        for proxy in ["a", "b"] {
+            for _ in 0..3 {
+                rotator.record_failure(proxy);
+            }
+        }
+        assert_eq!(rotator.next_proxy(), Some("a"));
+        assert_eq!(rotator.next_proxy(), Some("b"));
+        assert_eq!(rotator.next_proxy(), Some("a"));
+    }
+}
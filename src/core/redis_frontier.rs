@@ -0,0 +1,226 @@
+use std::time::Duration;
+use redis::{aio::ConnectionManager, Client, Script};
+
+use crate::error::BrowserError;
+use super::frontier::{Frontier, FrontierFuture, RateLimiter};
+
+
+const PUSH_LUA: &str = r#"
+if redis.call('SADD', KEYS[1], ARGV[1]) == 0 then
+    return 0
+end
+redis.call('RPUSH', KEYS[2], ARGV[1])
+return 1
+"#;
+
+const PUSH_KEYED_LUA: &str = r#"
+if redis.call('SADD', KEYS[1], ARGV[1]) == 0 then
+    return 0
+end
+redis.call('SADD', KEYS[2], ARGV[2])
+redis.call('RPUSH', KEYS[3], ARGV[2])
+return 1
+"#;
+
+fn redis_error(e: redis::RedisError) -> BrowserError {
+    BrowserError::Frontier(e.to_string())
+}
+
+async fn connect(url: &str) -> Result<ConnectionManager, BrowserError> {
+    let client = Client::open(url).map_err(redis_error)?;
+    client.get_connection_manager().await.map_err(redis_error)
+}
+
+#[derive(Clone)]
+pub struct RedisFrontier {
+    conn: ConnectionManager,
+    queue_key: String,
+    processing_key: String,
+    visited_key: String,
+    keys_key: String,
+}
+
+impl RedisFrontier {
+    pub async fn connect(url: &str, crawl: &str) -> Result<Self, BrowserError> {
+        Ok (
+            Self {
+                conn: connect(url).await?,
+                queue_key: format!("browser_bridge:{crawl}:queue"),
+                processing_key: format!("browser_bridge:{crawl}:processing"),
+                visited_key: format!("browser_bridge:{crawl}:visited"),
+                keys_key: format!("browser_bridge:{crawl}:keys")
+            }
+        )
+    }
+
+    pub async fn requeue_processing(&self) -> Result<usize, BrowserError> {
+        let mut conn = self.conn.clone();
+        let mut requeued = 0;
+        loop {
+            let url: Option<String> = redis::cmd("LMOVE")
+                .arg(&self.processing_key)
+                .arg(&self.queue_key)
+                .arg("RIGHT")
+                .arg("LEFT")
+                .query_async(&mut conn)
+                .await
+                .map_err(redis_error)?;
+            if url.is_none() {
+                return Ok(requeued);
+            }
+            requeued += 1;
+        }
+    }
+}
+
+impl Frontier for RedisFrontier {
+    fn push<'a>(&'a self, url: &'a str) -> FrontierFuture<'a, bool> {
+        Box::pin(async move {
+            let mut conn = self.conn.clone();
+            let added: i64 = Script::new(PUSH_LUA)
+                .key(&self.visited_key)
+                .key(&self.queue_key)
+                .arg(url)
+                .invoke_async(&mut conn)
+                .await
+                .map_err(redis_error)?;
+            Ok(added == 1)
+        })
+    }
+
+    fn push_keyed<'a>(&'a self, key: &'a str, url: &'a str) -> FrontierFuture<'a, bool> {
+        Box::pin(async move {
+            let mut conn = self.conn.clone();
+            let added: i64 = Script::new(PUSH_KEYED_LUA)
+                .key(&self.keys_key)
+                .key(&self.visited_key)
+                .key(&self.queue_key)
+                .arg(key)
+                .arg(url)
+                .invoke_async(&mut conn)
+                .await
+                .map_err(redis_error)?;
+            Ok(added == 1)
+        })
+    }
+
+    fn pop(&self) -> FrontierFuture<'_, Option<String>> {
+        Box::pin(async move {
+            let mut conn = self.conn.clone();
+            redis::cmd("LMOVE")
+                .arg(&self.queue_key)
+                .arg(&self.processing_key)
+                .arg("LEFT")
+                .arg("RIGHT")
+                .query_async(&mut conn)
+                .await
+                .map_err(redis_error)
+        })
+    }
+
+    fn ack<'a>(&'a self, url: &'a str) -> FrontierFuture<'a, ()> {
+        Box::pin(async move {
+            let mut conn = self.conn.clone();
+            redis::cmd("LREM")
+                .arg(&self.processing_key)
+                .arg(1)
+                .arg(url)
+                .query_async::<()>(&mut conn)
+                .await
+                .map_err(redis_error)
+        })
+    }
+
+    fn pending(&self) -> FrontierFuture<'_, usize> {
+        Box::pin(async move {
+            let mut conn = self.conn.clone();
+            redis::cmd("LLEN")
+                .arg(&self.queue_key)
+                .query_async(&mut conn)
+                .await
+                .map_err(redis_error)
+        })
+    }
+
+    fn is_visited<'a>(&'a self, url: &'a str) -> FrontierFuture<'a, bool> {
+        Box::pin(async move {
+            let mut conn = self.conn.clone();
+            redis::cmd("SISMEMBER")
+                .arg(&self.visited_key)
+                .arg(url)
+                .query_async(&mut conn)
+                .await
+                .map_err(redis_error)
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct RedisRateLimiter {
+    conn: ConnectionManager,
+    prefix: String,
+    interval: Duration,
+}
+
+impl RedisRateLimiter {
+    pub async fn connect(url: &str, crawl: &str, interval: Duration) -> Result<Self, BrowserError> {
+        Ok (
+            Self {
+                conn: connect(url).await?,
+                prefix: format!("browser_bridge:{crawl}:rate"),
+                interval
+            }
+        )
+    }
+}
+
+impl RateLimiter for RedisRateLimiter {
+    fn acquire<'a>(&'a self, domain: &'a str) -> FrontierFuture<'a, Duration> {
+        Box::pin(async move {
+            let mut conn = self.conn.clone();
+            let key = format!("{}:{domain}", self.prefix);
+            let granted: Option<String> = redis::cmd("SET")
+                .arg(&key)
+                .arg(1)
+                .arg("NX")
+                .arg("PX")
+                .arg(self.interval.as_millis().max(1) as u64)
+                .query_async(&mut conn)
+                .await
+                .map_err(redis_error)?;
+            if granted.is_some() {
+                return Ok(Duration::ZERO);
+            }
+            let ttl: i64 = redis::cmd("PTTL")
+                .arg(&key)
+                .query_async(&mut conn)
+                .await
+                .map_err(redis_error)?;
+            Ok(Duration::from_millis(ttl.max(1) as u64))
+        })
+    }
+
+    fn penalize<'a>(&'a self, domain: &'a str, wait: Duration) -> FrontierFuture<'a, ()> {
+        Box::pin(async move {
+            let mut conn = self.conn.clone();
+            let key = format!("{}:{domain}", self.prefix);
+            let ttl: i64 = redis::cmd("PTTL")
+                .arg(&key)
+                .query_async(&mut conn)
+                .await
+                .map_err(redis_error)?;
+            let wait = wait.as_millis().max(1) as i64;
+            if ttl >= wait {
+                return Ok(());
+            }
+            redis::cmd("SET")
+                .arg(&key)
+                .arg(1)
+                .arg("PX")
+                .arg(wait)
+                .query_async::<()>(&mut conn)
+                .await
+                .map_err(redis_error)
+        })
+    }
+}
@@ -0,0 +1,186 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use tokio::time::sleep;
+use chromiumoxide::Page;
+
+use crate::error::BrowserError;
+use super::browser::BrowserSession;
+use super::frontier::{url_host, wait_turn, RateLimiter};
+use super::network::MainResponse;
+
+
+static MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun",
+    "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"
+];
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+pub fn parse_http_date(value: &str) -> Option<SystemTime> {
+    let (day, month, year, time) = match value.split_once(", ") {
+        // obsolete RFC 850 form: Sunday, 06-Nov-94 08:49:37 GMT
+        Some((_, rest)) if rest.contains('-') => {
+            let mut parts = rest.split_whitespace();
+            let mut date = parts.next()?.split('-');
+            let (day, month, year) = (date.next()?, date.next()?, date.next()?);
+            let year: i64 = year.parse().ok()?;
+            let year = match year {
+                0..=69 => year + 2000,
+                70..=99 => year + 1900,
+                _ => year
+            };
+            (day, month, year, parts.next()?)
+        },
+        // IMF-fixdate: Sun, 06 Nov 1994 08:49:37 GMT
+        Some((_, rest)) => {
+            let mut parts = rest.split_whitespace();
+            let (day, month) = (parts.next()?, parts.next()?);
+            (day, month, parts.next()?.parse().ok()?, parts.next()?)
+        },
+        // obsolete asctime form: Sun Nov  6 08:49:37 1994
+        None => {
+            let mut parts = value.split_whitespace().skip(1);
+            let (month, day, time) = (parts.next()?, parts.next()?, parts.next()?);
+            (day, month, parts.next()?.parse().ok()?, time)
+        }
+    };
+    let day: i64 = day.parse().ok()?;
+    let month = MONTHS.iter().position(|m| *m == month)? as i64 + 1;
+    let mut time = time.split(':').map(|t| t.parse::<i64>().ok());
+    let (hours, minutes, seconds) = (time.next()??, time.next()??, time.next()??);
+    let secs = days_from_civil(year, month, day) * 86400 + hours * 3600 + minutes * 60 + seconds;
+    u64::try_from(secs).ok()
+        .map(|s| UNIX_EPOCH + Duration::from_secs(s))
+}
+
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = parse_http_date(value)?;
+    Some(at.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+impl MainResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.as_object()?
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .and_then(|(_, value)| value.as_str())
+    }
+
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.header("retry-after").and_then(parse_retry_after)
+    }
+
+    pub fn is_captcha_challenge(&self) -> bool {
+        self.header("cf-mitigated").is_some_and(|v| v.eq_ignore_ascii_case("challenge"))
+            || self.header("x-amzn-waf-action").is_some_and(|v| v.eq_ignore_ascii_case("captcha"))
+            || self.header("location").is_some_and(|v| v.to_ascii_lowercase().contains("captcha"))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub statuses: Vec<i64>,
+    pub default_wait: u64,
+    pub max_wait: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            statuses: vec![429, 503],
+            default_wait: 5000,
+            max_wait: 60000
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn wait_for(&self, response: &MainResponse) -> Option<Duration> {
+        if !self.statuses.contains(&response.status) {
+            return None;
+        }
+        let wait = response.retry_after()
+            .unwrap_or(Duration::from_millis(self.default_wait));
+        Some(wait.min(Duration::from_millis(self.max_wait)))
+    }
+}
+
+impl BrowserSession {
+    pub async fn open_with_retry(
+        &self, url: &str, policy: &RetryPolicy, limiter: Option<&dyn RateLimiter>
+    ) -> Result<(Page, MainResponse), BrowserError> {
+        let mut attempt = 0;
+        loop {
+            if let Some(limiter) = limiter {
+                wait_turn(limiter, url).await?;
+            }
+            let (page, response) = self.open_with_response(url).await?;
+            if response.is_captcha_challenge() {
+                let _ = page.close().await;
+                return Err(BrowserError::BlockedCaptcha);
+            }
+            let wait = match policy.wait_for(&response) {
+                Some(wait) if attempt < policy.max_retries => wait,
+                _ => return Ok((page, response))
+            };
+            let _ = page.close().await;
+            attempt += 1;
+            match (limiter, url_host(url)) {
+                (Some(limiter), Some(host)) => limiter.penalize(host, wait).await?,
+                _ => sleep(wait).await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPECTED: u64 = 784111777;
+
+    #[test]
+    fn parses_all_http_date_formats() {
+        for value in [
+            "Sun, 06 Nov 1994 08:49:37 GMT",
+            "Sunday, 06-Nov-94 08:49:37 GMT",
+            "Sun Nov  6 08:49:37 1994"
+        ] {
+            assert_eq!(
+                parse_http_date(value),
+                Some(UNIX_EPOCH + Duration::from_secs(EXPECTED)),
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_http_dates() {
+        for value in ["", "Sun, 06 Foo 1994 08:49:37 GMT", "Sun, 06 Nov 1994 08:49 GMT", "tomorrow"] {
+            assert_eq!(parse_http_date(value), None, "{value}");
+        }
+    }
+
+    #[test]
+    fn retry_after_accepts_seconds_and_dates() {
+        assert_eq!(parse_retry_after(" 120 "), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("Sun, 06 Nov 1994 08:49:37 GMT"), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("-5"), None);
+        let ahead = parse_retry_after("Fri, 01 Jan 2100 00:00:00 GMT").unwrap();
+        assert!(ahead > Duration::from_secs(86400 * 365));
+    }
+}
@@ -0,0 +1,184 @@
+use std::{future::Future, time::Duration};
+use serde::{Deserialize, Serialize};
+use tokio::time::sleep;
+use aws_sdk_s3::{
+    primitives::ByteStream,
+    types::{CompletedMultipartUpload, CompletedPart},
+    Client
+};
+
+use crate::error::BrowserError;
+use super::sink::{content_type, ArtifactSink, SinkFuture};
+
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct S3SinkConfig {
+    pub bucket: String,
+    pub prefix: String,
+    pub endpoint: Option<String>,
+    pub region: Option<String>,
+    pub force_path_style: bool,
+    pub retries: u32,
+    pub retry_backoff: u64,
+    pub multipart_threshold: usize,
+    pub part_size: usize,
+}
+
+impl Default for S3SinkConfig {
+    fn default() -> Self {
+        Self {
+            bucket: String::new(),
+            prefix: String::new(),
+            endpoint: None,
+            region: None,
+            force_path_style: false,
+            retries: 3,
+            retry_backoff: 500,
+            multipart_threshold: 16 * 1024 * 1024,
+            part_size: 8 * 1024 * 1024
+        }
+    }
+}
+
+pub struct S3Sink {
+    client: Client,
+    config: S3SinkConfig,
+}
+
+impl S3Sink {
+    pub async fn new(config: S3SinkConfig) -> Self {
+        let mut loader = aws_config::defaults(aws_config::BehaviorVersion::latest());
+        if let Some(region) = &config.region {
+            loader = loader.region(aws_config::Region::new(region.clone()));
+        }
+        let shared = loader.load().await;
+        let mut builder = aws_sdk_s3::config::Builder::from(&shared)
+            .force_path_style(config.force_path_style);
+        if let Some(endpoint) = &config.endpoint {
+            builder = builder.endpoint_url(endpoint);
+        }
+
+        Self {
+            client: Client::from_conf(builder.build()),
+            config
+        }
+    }
+
+    fn object_key(&self, key: &str) -> String {
+        match self.config.prefix.trim_matches('/') {
+            "" => key.to_owned(),
+            prefix => format!("{prefix}/{key}")
+        }
+    }
+
+    async fn with_retry<T, F, Fut>(&self, op: F) -> Result<T, BrowserError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, String>>
+    {
+        let mut attempt = 0;
+        loop {
+            match op().await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt >= self.config.retries => {
+                    return Err(BrowserError::Sink(e));
+                },
+                Err(_) => {
+                    attempt += 1;
+                    sleep(
+                        Duration::from_millis(self.config.retry_backoff * 2u64.pow(attempt - 1))
+                    ).await;
+                }
+            }
+        }
+    }
+
+    async fn put_single(&self, key: &str, data: Vec<u8>) -> Result<(), BrowserError> {
+        self.with_retry(|| async {
+            self.client.put_object()
+                .bucket(&self.config.bucket)
+                .key(key)
+                .content_type(content_type(key))
+                .body(ByteStream::from(data.clone()))
+                .send()
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }).await
+    }
+
+    async fn put_multipart(&self, key: &str, data: &[u8]) -> Result<(), BrowserError> {
+        let upload_id = self.with_retry(|| async {
+            self.client.create_multipart_upload()
+                .bucket(&self.config.bucket)
+                .key(key)
+                .content_type(content_type(key))
+                .send()
+                .await
+                .map_err(|e| e.to_string())?
+                .upload_id()
+                .map(str::to_owned)
+                .ok_or_else(|| "missing upload id".to_owned())
+        }).await?;
+        let mut parts = Vec::new();
+        for (idx, chunk) in data.chunks(self.config.part_size.max(5 * 1024 * 1024)).enumerate() {
+            let part_number = idx as i32 + 1;
+            let uploaded = self.with_retry(|| async {
+                self.client.upload_part()
+                    .bucket(&self.config.bucket)
+                    .key(key)
+                    .upload_id(&upload_id)
+                    .part_number(part_number)
+                    .body(ByteStream::from(chunk.to_vec()))
+                    .send()
+                    .await
+                    .map_err(|e| e.to_string())
+            }).await;
+            match uploaded {
+                Ok(output) => parts.push(
+                    CompletedPart::builder()
+                        .set_e_tag(output.e_tag().map(str::to_owned))
+                        .part_number(part_number)
+                        .build()
+                ),
+                Err(e) => {
+                    let _ = self.client.abort_multipart_upload()
+                        .bucket(&self.config.bucket)
+                        .key(key)
+                        .upload_id(&upload_id)
+                        .send()
+                        .await;
+                    return Err(e);
+                }
+            }
+        }
+        let completed = CompletedMultipartUpload::builder()
+            .set_parts(Some(parts))
+            .build();
+        self.with_retry(|| async {
+            self.client.complete_multipart_upload()
+                .bucket(&self.config.bucket)
+                .key(key)
+                .upload_id(&upload_id)
+                .multipart_upload(completed.clone())
+                .send()
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }).await
+    }
+}
+
+impl ArtifactSink for S3Sink {
+    fn put<'a>(&'a self, key: &'a str, data: Vec<u8>) -> SinkFuture<'a> {
+        Box::pin(async move {
+            let key = self.object_key(key);
+            if data.len() > self.config.multipart_threshold {
+                self.put_multipart(&key, &data).await
+            } else {
+                self.put_single(&key, data).await
+            }
+        })
+    }
+}
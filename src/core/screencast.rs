@@ -0,0 +1,331 @@
+use std::{
+    net::SocketAddr,
+    sync::Arc,
+    time::Duration
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    runtime::Handle,
+    sync::{mpsc, watch},
+    task::JoinHandle,
+    time::timeout
+};
+use tokio_stream::StreamExt;
+use chromiumoxide::{
+    cdp::browser_protocol::page::{
+        EventScreencastFrame,
+        ScreencastFrameAckParams,
+        StartScreencastFormat,
+        StartScreencastParams,
+        StopScreencastParams
+    },
+    Page
+};
+
+use crate::error::BrowserError;
+use super::handoff::OperatorCommand;
+
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ScreencastOptions {
+    pub quality: i64,
+    pub max_width: Option<i64>,
+    pub max_height: Option<i64>,
+    pub every_nth_frame: i64,
+}
+
+impl Default for ScreencastOptions {
+    fn default() -> Self {
+        Self {
+            quality: 60,
+            max_width: Some(1280),
+            max_height: Some(720),
+            every_nth_frame: 1
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ServeOptions {
+    pub addr: String,
+    pub token: String,
+    pub control: Option<mpsc::Sender<OperatorCommand>>,
+}
+
+impl Default for ServeOptions {
+    fn default() -> Self {
+        Self {
+            addr: "127.0.0.1:0".into(),
+            token: format!("{:032x}", rand::random::<u128>()),
+            control: None
+        }
+    }
+}
+
+pub type Frame = Arc<Vec<u8>>;
+
+struct HttpRequest {
+    method: String,
+    path: String,
+    token: Option<String>,
+    body: Vec<u8>,
+}
+
+const UNAUTHORIZED: &str = "HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+
+const NOT_FOUND: &str = "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+
+const NO_CONTENT: &str = "HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n";
+
+const BAD_REQUEST: &str = "HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+
+const MAX_REQUEST_HEAD: usize = 16 * 1024;
+
+const MAX_REQUEST_BODY: usize = 64 * 1024;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Idle or trickling clients are dropped instead of holding a task and socket.
+async fn read_request_within(stream: &mut TcpStream, limit: Duration) -> Option<HttpRequest> {
+    timeout(limit, read_request(stream)).await.ok().flatten()
+}
+
+async fn read_request(stream: &mut TcpStream) -> Option<HttpRequest> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 1024];
+    let head_end = loop {
+        if let Some(idx) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break idx;
+        }
+        let n = stream.read(&mut buf).await.ok()?;
+        if n == 0 || data.len() + n > MAX_REQUEST_HEAD {
+            return None;
+        }
+        data.extend_from_slice(&buf[..n]);
+    };
+    let mut body = data.split_off(head_end + 4);
+    let head = String::from_utf8_lossy(&data[..head_end]);
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_owned();
+    let target = request_line.next()?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut token = query.split('&')
+        .find_map(|pair| pair.strip_prefix("token="))
+        .map(str::to_owned);
+    let mut content_length = 0;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = name.trim();
+        if name.eq_ignore_ascii_case("authorization") {
+            if let Some(bearer) = value.trim().strip_prefix("Bearer ") {
+                token = Some(bearer.trim().to_owned());
+            }
+        } else if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.trim().parse().ok()?;
+        }
+    }
+    if content_length > MAX_REQUEST_BODY {
+        return None;
+    }
+    while body.len() < content_length {
+        let n = stream.read(&mut buf).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        body.extend_from_slice(&buf[..n]);
+    }
+    body.truncate(content_length);
+    Some(HttpRequest { method, path: path.to_owned(), token, body })
+}
+
+pub struct Screencast {
+    page: Page,
+    frames: watch::Receiver<Option<Frame>>,
+    handle: JoinHandle<()>,
+}
+
+impl Screencast {
+    pub async fn start(page: &Page, options: ScreencastOptions) -> Result<Self, BrowserError> {
+        let mut events = page.event_listener::<EventScreencastFrame>().await?;
+        let (sender, frames) = watch::channel(None);
+        let task_page = page.clone();
+        let handle = tokio::task::spawn(async move {
+            while let Some(event) = events.next().await {
+                let _ = task_page.execute(ScreencastFrameAckParams::new(event.session_id)).await;
+                let data: &str = event.data.as_ref();
+                if let Ok(jpeg) = STANDARD.decode(data) {
+                    let _ = sender.send(Some(Arc::new(jpeg)));
+                }
+            }
+        });
+        page.execute(StartScreencastParams {
+            format: Some(StartScreencastFormat::Jpeg),
+            quality: Some(options.quality),
+            max_width: options.max_width,
+            max_height: options.max_height,
+            every_nth_frame: Some(options.every_nth_frame)
+        }).await?;
+
+        Ok (
+            Self {
+                page: page.clone(),
+                frames,
+                handle
+            }
+        )
+    }
+
+    pub fn latest_frame(&self) -> Option<Frame> {
+        self.frames.borrow().clone()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<Option<Frame>> {
+        self.frames.clone()
+    }
+
+    pub async fn serve_mjpeg(&self, options: ServeOptions) -> Result<(SocketAddr, JoinHandle<()>), BrowserError> {
+        let listener = TcpListener::bind(&options.addr).await
+            .map_err(|_| BrowserError::NetworkIO)?;
+        let local_addr = listener.local_addr()
+            .map_err(|_| BrowserError::NetworkIO)?;
+        let frames = self.subscribe();
+        let token = Arc::new(options.token);
+        let control = options.control;
+        let handle = tokio::task::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut frames = frames.clone();
+                let token = token.clone();
+                let control = control.clone();
+                tokio::task::spawn(async move {
+                    let Some(request) = read_request_within(&mut stream, REQUEST_TIMEOUT).await else {
+                        return;
+                    };
+                    if request.token.as_deref() != Some(token.as_str()) {
+                        let _ = stream.write_all(UNAUTHORIZED.as_bytes()).await;
+                        return;
+                    }
+                    match (request.method.as_str(), request.path.as_str(), &control) {
+                        ("GET", "/", _) => {},
+                        ("POST", "/control", Some(control)) => {
+                            let response = match serde_json::from_slice::<OperatorCommand>(&request.body) {
+                                Ok(command) => match control.send(command).await {
+                                    Ok(_) => NO_CONTENT,
+                                    Err(_) => NOT_FOUND
+                                },
+                                Err(_) => BAD_REQUEST
+                            };
+                            let _ = stream.write_all(response.as_bytes()).await;
+                            return;
+                        },
+                        _ => {
+                            let _ = stream.write_all(NOT_FOUND.as_bytes()).await;
+                            return;
+                        }
+                    }
+                    let header = "HTTP/1.1 200 OK\r\n\
+                        Cache-Control: no-cache\r\n\
+                        Connection: close\r\n\
+                        Content-Type: multipart/x-mixed-replace; boundary=frame\r\n\r\n";
+                    if stream.write_all(header.as_bytes()).await.is_err() {
+                        return;
+                    }
+                    loop {
+                        let frame = frames.borrow_and_update().clone();
+                        if let Some(frame) = frame {
+                            let part = format!(
+                                "--frame\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\n\r\n",
+                                frame.len()
+                            );
+                            if stream.write_all(part.as_bytes()).await.is_err()
+                                || stream.write_all(&frame).await.is_err()
+                                || stream.write_all(b"\r\n").await.is_err() {
+                                return;
+                            }
+                        }
+                        if frames.changed().await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+        Ok((local_addr, handle))
+    }
+}
+
+impl Drop for Screencast {
+    fn drop(&mut self) {
+        self.handle.abort();
+        let Ok(runtime) = Handle::try_current() else {
+            return;
+        };
+        let page = self.page.clone();
+        runtime.spawn(async move {
+            let _ = page.execute(StopScreencastParams::default()).await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn parse(raw: &'static str) -> Option<HttpRequest> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::task::spawn(async move {
+            let mut client = TcpStream::connect(addr).await.unwrap();
+            client.write_all(raw.as_bytes()).await.unwrap();
+        });
+        let (mut stream, _) = listener.accept().await.unwrap();
+        read_request(&mut stream).await
+    }
+
+    #[tokio::test]
+    async fn reads_token_from_query_or_bearer() {
+        let request = parse("GET /?token=abc HTTP/1.1\r\nHost: x\r\n\r\n").await.unwrap();
+        assert_eq!((request.method.as_str(), request.path.as_str()), ("GET", "/"));
+        assert_eq!(request.token.as_deref(), Some("abc"));
+        let request = parse("GET / HTTP/1.1\r\nAuthorization: Bearer xyz\r\n\r\n").await.unwrap();
+        assert_eq!(request.token.as_deref(), Some("xyz"));
+        assert!(parse("GET / HTTP/1.1\r\n\r\n").await.unwrap().token.is_none());
+    }
+
+    #[tokio::test]
+    async fn reads_control_body() {
+        let request = parse(
+            "POST /control HTTP/1.1\r\nContent-Length: 35\r\n\r\n{\"command\":\"click\",\"x\":1.0,\"y\":2.0}"
+        ).await.unwrap();
+        assert_eq!(request.path, "/control");
+        let command: OperatorCommand = serde_json::from_slice(&request.body).unwrap();
+        assert!(matches!(command, OperatorCommand::Click { x, y } if x == 1.0 && y == 2.0));
+    }
+
+    #[tokio::test]
+    async fn drops_silent_clients() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = tokio::task::spawn(async move {
+            let mut client = TcpStream::connect(addr).await.unwrap();
+            client.write_all(b"GET / HTTP/1.1\r\n").await.unwrap();
+            let _ = client.read(&mut [0u8; 1]).await;
+        });
+        let (mut stream, _) = listener.accept().await.unwrap();
+        assert!(read_request_within(&mut stream, Duration::from_millis(50)).await.is_none());
+        drop(stream);
+        client.await.unwrap();
+    }
+
+    #[test]
+    fn serves_on_loopback_with_a_token_by_default() {
+        let options = ServeOptions::default();
+        assert!(options.addr.starts_with("127.0.0.1:"));
+        assert_eq!(options.token.len(), 32);
+    }
+}
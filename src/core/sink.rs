@@ -0,0 +1,241 @@
+use std::{
+    collections::HashSet,
+    fs::{self, OpenOptions},
+    future::Future,
+    io::{self, Write},
+    path::{Component, Path, PathBuf},
+    pin::Pin,
+    sync::Mutex
+};
+use serde::Serialize;
+
+use crate::error::BrowserError;
+use super::artifacts::JobArtifacts;
+
+
+pub type SinkFuture<'a> = Pin<Box<dyn Future<Output = Result<(), BrowserError>> + Send + 'a>>;
+
+pub trait ArtifactSink: Send + Sync {
+    fn put<'a>(&'a self, key: &'a str, data: Vec<u8>) -> SinkFuture<'a>;
+}
+
+pub fn content_type(key: &str) -> &'static str {
+    match key.rsplit_once('.').map(|(_, ext)| ext) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("json" | "har") => "application/json",
+        Some("jsonl") => "application/x-ndjson",
+        _ => "application/octet-stream"
+    }
+}
+
+pub fn to_jsonl<T: Serialize>(records: &[T]) -> Result<Vec<u8>, BrowserError> {
+    let mut out = Vec::new();
+    for record in records {
+        serde_json::to_writer(&mut out, record)
+            .map_err(|_| BrowserError::Serialization)?;
+        out.push(b'\n');
+    }
+    Ok(out)
+}
+
+pub fn idempotency_key(parts: &[&str]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for (idx, part) in parts.iter().enumerate() {
+        if idx > 0 {
+            hash ^= 0x1f;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        for byte in part.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{hash:016x}")
+}
+
+#[derive(Clone, Debug)]
+pub struct DirSink {
+    root: PathBuf,
+}
+
+impl DirSink {
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self { root: root.as_ref().to_path_buf() }
+    }
+}
+
+impl ArtifactSink for DirSink {
+    fn put<'a>(&'a self, key: &'a str, data: Vec<u8>) -> SinkFuture<'a> {
+        Box::pin(async move {
+            let relative = Path::new(key);
+            if key.is_empty() || !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+                return Err(BrowserError::Sink(format!("invalid artifact key: {key}")));
+            }
+            let path = self.root.join(relative);
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await
+                    .map_err(|e| BrowserError::Sink(e.to_string()))?;
+            }
+            tokio::fs::write(&path, data).await
+                .map_err(|e| BrowserError::Sink(e.to_string()))
+        })
+    }
+}
+
+impl JobArtifacts {
+    pub fn idempotent(parts: &[&str]) -> Self {
+        let key = idempotency_key(parts);
+        Self::new(&key)
+            .with_metadata("idempotency_key", key.as_str().into())
+    }
+
+    pub async fn upload(&self, sink: &dyn ArtifactSink) -> Result<Vec<String>, BrowserError> {
+        let files = self.files()
+            .map_err(|e| BrowserError::Sink(e.to_string()))?;
+        let mut keys = Vec::with_capacity(files.len());
+        for (name, data) in files {
+            let key = format!("{}/{name}", self.job_id);
+            sink.put(&key, data).await?;
+            keys.push(key);
+        }
+        Ok(keys)
+    }
+}
+
+pub struct DeliveryLedger {
+    path: PathBuf,
+    delivered: Mutex<HashSet<String>>,
+    claimed: Mutex<HashSet<String>>,
+}
+
+impl DeliveryLedger {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let delivered = match fs::read_to_string(&path) {
+            Ok(data) => data.lines()
+                .filter(|l| !l.is_empty())
+                .map(str::to_owned)
+                .collect(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => HashSet::new(),
+            Err(e) => return Err(e)
+        };
+
+        Ok (
+            Self {
+                path,
+                delivered: Mutex::new(delivered),
+                claimed: Mutex::new(HashSet::new())
+            }
+        )
+    }
+
+    pub fn is_delivered(&self, key: &str) -> bool {
+        self.delivered.lock().unwrap().contains(key)
+    }
+
+    fn claim(&self, key: &str) -> bool {
+        let delivered = self.delivered.lock().unwrap();
+        !delivered.contains(key) && self.claimed.lock().unwrap().insert(key.to_owned())
+    }
+
+    fn release(&self, key: &str) {
+        self.claimed.lock().unwrap().remove(key);
+    }
+
+    pub fn mark_delivered(&self, key: &str) -> io::Result<()> {
+        let mut delivered = self.delivered.lock().unwrap();
+        if delivered.contains(key) {
+            return Ok(());
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{key}")?;
+        file.sync_data()?;
+        delivered.insert(key.to_owned());
+        Ok(())
+    }
+}
+
+pub struct DedupSink<S> {
+    inner: S,
+    ledger: DeliveryLedger,
+}
+
+impl<S: ArtifactSink> DedupSink<S> {
+    pub fn new(inner: S, ledger: DeliveryLedger) -> Self {
+        Self { inner, ledger }
+    }
+
+    pub fn ledger(&self) -> &DeliveryLedger {
+        &self.ledger
+    }
+}
+
+impl<S: ArtifactSink> ArtifactSink for DedupSink<S> {
+    fn put<'a>(&'a self, key: &'a str, data: Vec<u8>) -> SinkFuture<'a> {
+        Box::pin(async move {
+            if !self.ledger.claim(key) {
+                return Ok(());
+            }
+            let result = match self.inner.put(key, data).await {
+                Ok(_) => self.ledger.mark_delivered(key)
+                    .map_err(|e| BrowserError::Sink(e.to_string())),
+                Err(e) => Err(e)
+            };
+            self.ledger.release(key);
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountingSink(AtomicUsize);
+
+    impl ArtifactSink for CountingSink {
+        fn put<'a>(&'a self, _key: &'a str, _data: Vec<u8>) -> SinkFuture<'a> {
+            Box::pin(async move {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                tokio::task::yield_now().await;
+                Ok(())
+            })
+        }
+    }
+
+    fn scratch(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("browser_bridge_sink_{}_{name}", std::process::id()))
+    }
+
+    #[tokio::test]
+    async fn dir_sink_rejects_escaping_keys() {
+        let sink = DirSink::new(scratch("dir"));
+        for key in ["../escape", "/abs/key", "a/../../b", "./x", ""] {
+            assert!(sink.put(key, Vec::new()).await.is_err(), "{key}");
+        }
+        assert!(sink.put("job/page.html", b"ok".to_vec()).await.is_ok());
+        let _ = fs::remove_dir_all(scratch("dir"));
+    }
+
+    #[tokio::test]
+    async fn dedup_sink_delivers_once_under_concurrency() {
+        let path = scratch("ledger");
+        let _ = fs::remove_file(&path);
+        let sink = DedupSink::new(CountingSink(AtomicUsize::new(0)), DeliveryLedger::open(&path).unwrap());
+        for _ in 0..3 {
+            sink.put("job/data.json", Vec::new()).await.unwrap();
+        }
+        let (a, b) = tokio::join!(sink.put("job/x", Vec::new()), sink.put("job/x", Vec::new()));
+        a.unwrap();
+        b.unwrap();
+        assert_eq!(sink.inner.0.load(Ordering::SeqCst), 2);
+        assert!(sink.ledger().is_delivered("job/x"));
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::BTreeSet;
+use serde::{Deserialize, Serialize};
+use chromiumoxide::cdp::browser_protocol::{
+    browser::BrowserContextId,
+    network::CookieParam,
+    storage::{
+        ClearDataForOriginParams,
+        GetUsageAndQuotaParams,
+        SetCookiesParams,
+        StorageType
+    }
+};
+
+use crate::error::BrowserError;
+use super::browser::BrowserSession;
+
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct OriginUsage {
+    pub origin: String,
+    pub usage: f64,
+    pub quota: f64,
+    pub breakdown: Vec<(String, f64)>,
+}
+
+fn cookie_domain_origin(domain: &str) -> String {
+    format!("https://{}", domain.trim_start_matches('.'))
+}
+
+impl BrowserSession {
+    pub async fn storage_origins(&self) -> Result<Vec<String>, BrowserError> {
+        let mut origins = BTreeSet::new();
+        for cookie in self.browser.get_cookies().await? {
+            origins.insert(cookie_domain_origin(&cookie.domain));
+        }
+        for page in self.browser.pages().await? {
+            if let Ok(Some(url)) = page.url().await {
+                if url.starts_with("http") {
+                    if let Some(end) = url.match_indices('/').nth(2).map(|(i, _)| i) {
+                        origins.insert(url[..end].to_owned());
+                    }
+                }
+            }
+        }
+        Ok(origins.into_iter().collect())
+    }
+
+    pub async fn origin_usage(&self, origin: &str) -> Result<OriginUsage, BrowserError> {
+        let usage = self.browser.execute(
+            GetUsageAndQuotaParams::new(origin)
+        ).await?.result;
+
+        Ok (
+            OriginUsage {
+                origin: origin.to_owned(),
+                usage: usage.usage,
+                quota: usage.quota,
+                breakdown: usage.usage_breakdown.into_iter()
+                    .filter(|u| u.usage > 0.0)
+                    .map(|u| (u.storage_type.as_ref().to_owned(), u.usage))
+                    .collect()
+            }
+        )
+    }
+
+    pub async fn storage_report(&self) -> Result<Vec<OriginUsage>, BrowserError> {
+        let mut report = Vec::new();
+        for origin in self.storage_origins().await? {
+            if let Ok(usage) = self.origin_usage(&origin).await {
+                report.push(usage);
+            }
+        }
+        report.sort_by(|a, b| b.usage.total_cmp(&a.usage));
+        Ok(report)
+    }
+
+    pub async fn clear_origin_data(&self, origin: &str, types: &[StorageType]) -> Result<(), BrowserError> {
+        let storage_types = match types.is_empty() {
+            true => StorageType::All.as_ref().to_owned(),
+            false => types.iter()
+                .map(|t| t.as_ref())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        self.browser.execute(
+            ClearDataForOriginParams::new(origin, storage_types)
+        ).await?;
+        Ok(())
+    }
+
+    pub async fn set_cookies_global(&self, cookies: Vec<CookieParam>) -> Result<(), BrowserError> {
+        self.set_cookies_in_context(cookies, None).await
+    }
+
+    pub async fn set_cookies_in_context(
+        &self, cookies: Vec<CookieParam>, context_id: Option<BrowserContextId>
+    ) -> Result<(), BrowserError> {
+        let mut params = SetCookiesParams::new(cookies);
+        params.browser_context_id = context_id;
+        self.browser.execute(params).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    time::Duration
+};
+use serde::{Deserialize, Serialize};
+use tokio::time::timeout;
+use tokio_stream::{Stream, StreamExt};
+use chromiumoxide::{
+    cdp::browser_protocol::dom::SetFileInputFilesParams,
+    Page
+};
+
+use crate::error::BrowserError;
+use super::bindings::{binding_payloads, inject_script, unique_binding_name};
+
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UploadProgress {
+    pub id: u64,
+    pub url: String,
+    pub loaded: f64,
+    pub total: f64,
+    pub done: bool,
+    pub error: bool,
+}
+
+impl UploadProgress {
+    pub fn percent(&self) -> Option<f64> {
+        (self.total > 0.0).then(|| self.loaded / self.total * 100.0)
+    }
+}
+
+const UPLOAD_PROGRESS_JS: &str = r##"
+(() => {
+    if (window.__bbUploadWatched) return;
+    window.__bbUploadWatched = true;
+    const send = window[__BINDING__];
+    let counter = 0;
+    const report = (id, url, loaded, total, done, error) => send(JSON.stringify({
+        id, url: String(url), loaded, total, done, error
+    }));
+    const open = XMLHttpRequest.prototype.open;
+    XMLHttpRequest.prototype.open = function (method, url, ...rest) {
+        this.__bbUrl = url;
+        return open.call(this, method, url, ...rest);
+    };
+    const xhrSend = XMLHttpRequest.prototype.send;
+    XMLHttpRequest.prototype.send = function (body) {
+        if (body) {
+            const id = ++counter;
+            const url = this.__bbUrl;
+            this.upload.addEventListener('progress', e => report(id, url, e.loaded, e.total, false, false));
+            this.upload.addEventListener('load', e => report(id, url, e.loaded, e.total, true, false));
+            this.upload.addEventListener('error', e => report(id, url, e.loaded, e.total, true, true));
+            this.upload.addEventListener('abort', e => report(id, url, e.loaded, e.total, true, true));
+        }
+        return xhrSend.call(this, body);
+    };
+    const fetch = window.fetch;
+    window.fetch = function (input, init) {
+        const body = init && init.body;
+        if (!body) return fetch.apply(this, arguments);
+        const id = ++counter;
+        const url = input instanceof Request ? input.url : input;
+        const total = body.size || body.byteLength || body.length || 0;
+        report(id, url, 0, total, false, false);
+        return fetch.apply(this, arguments).then(
+            r => { report(id, url, total, total, true, false); return r; },
+            e => { report(id, url, 0, total, true, true); throw e; }
+        );
+    };
+})()
+"##;
+
+pub async fn set_input_files<P: AsRef<Path>>(page: &Page, selector: &str, files: &[P]) -> Result<(), BrowserError> {
+    let element = page.find_element(selector).await?;
+    let params = SetFileInputFilesParams::builder()
+        .files(files.iter().map(|f|
+            std::path::absolute(f.as_ref())
+                .unwrap_or_else(|_| f.as_ref().to_path_buf())
+                .to_string_lossy()
+                .into_owned()
+        ))
+        .backend_node_id(element.backend_node_id)
+        .build()
+        .map_err(|_| BrowserError::ChromeInternal)?;
+    page.execute(params).await?;
+    Ok(())
+}
+
+pub async fn watch_uploads(page: &Page) -> Result<impl Stream<Item = UploadProgress>, BrowserError> {
+    let name = unique_binding_name("uploads");
+    let payloads = binding_payloads(page, &name).await?;
+    inject_script(
+        page,
+        UPLOAD_PROGRESS_JS.replace("__BINDING__", &format!("'{name}'"))
+    ).await?;
+
+    Ok(payloads.filter_map(|p| serde_json::from_str(&p).ok()))
+}
+
+pub async fn wait_uploads<S>(progress: &mut S, stall_timeout: Duration) -> Result<Vec<UploadProgress>, BrowserError>
+where
+    S: Stream<Item = UploadProgress> + Unpin
+{
+    let mut uploads = BTreeMap::new();
+    loop {
+        let Some(event) = timeout(stall_timeout, progress.next()).await? else {
+            break;
+        };
+        uploads.insert(event.id, event);
+        if uploads.values().all(|u| u.done) {
+            break;
+        }
+    }
+    Ok(uploads.into_values().collect())
+}
@@ -0,0 +1,78 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tokio::time::sleep;
+use chromiumoxide::Page;
+
+use crate::error::BrowserError;
+use super::browser::BrowserSession;
+
+
+pub static DEFAULT_WARMUP_SITES: [&str; 4] = [
+    "https://www.wikipedia.org/",
+    "https://www.bing.com/",
+    "https://www.reddit.com/",
+    "https://www.bbc.com/"
+];
+
+pub static DEFAULT_CONSENT_SELECTORS: [&str; 6] = [
+    "#onetrust-accept-btn-handler",
+    "#L2AGLb",
+    "button#accept-all",
+    "button[aria-label='Accept all']",
+    "button[mode='primary']",
+    ".fc-cta-consent"
+];
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WarmupPlan {
+    pub sites: Vec<String>,
+    pub consent_selectors: Vec<String>,
+    pub dwell: u64,
+    pub scroll: bool,
+}
+
+impl Default for WarmupPlan {
+    fn default() -> Self {
+        Self {
+            sites: DEFAULT_WARMUP_SITES.into_iter()
+                .map(|v| v.into())
+                .collect(),
+            consent_selectors: DEFAULT_CONSENT_SELECTORS.into_iter()
+                .map(|v| v.into())
+                .collect(),
+            dwell: 1500,
+            scroll: true
+        }
+    }
+}
+
+async fn accept_consent(page: &Page, selectors: &[String]) -> bool {
+    for selector in selectors {
+        if let Ok(el) = page.find_element(selector.as_str()).await {
+            if el.click().await.is_ok() {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+impl BrowserSession {
+    pub async fn warm_up(&self, plan: &WarmupPlan) -> Result<(), BrowserError> {
+        for site in &plan.sites {
+            let page = self.open(site).await?;
+            accept_consent(&page, &plan.consent_selectors).await;
+            if plan.scroll {
+                let _ = page.evaluate(
+                    "window.scrollBy(0, document.body.scrollHeight / 3)"
+                ).await;
+            }
+            sleep(
+                Duration::from_millis(plan.dwell)
+            ).await;
+            let _ = page.close().await;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,254 @@
+use serde::{Deserialize, Serialize};
+use chromiumoxide::cdp::browser_protocol::network::ResourceType;
+
+use super::network::NetworkEntry;
+
+
+static PHASE_COLORS: [(&str, &str); 7] = [
+    ("blocked", "#c8c8c8"),
+    ("dns", "#4fa3a5"),
+    ("connect", "#e6a23c"),
+    ("ssl", "#b36ae2"),
+    ("send", "#3b8ee8"),
+    ("wait", "#5cb85c"),
+    ("download", "#2f6fb0")
+];
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct WaterfallPhases {
+    pub blocked: f64,
+    pub dns: f64,
+    pub connect: f64,
+    pub ssl: f64,
+    pub send: f64,
+    pub wait: f64,
+    pub download: f64,
+}
+
+impl WaterfallPhases {
+    fn as_array(&self) -> [f64; 7] {
+        [self.blocked, self.dns, self.connect, self.ssl, self.send, self.wait, self.download]
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WaterfallRow {
+    pub url: String,
+    pub resource_type: Option<ResourceType>,
+    pub status: Option<i64>,
+    pub start: f64,
+    pub end: f64,
+    pub phases: WaterfallPhases,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Waterfall {
+    pub total: f64,
+    pub rows: Vec<WaterfallRow>,
+}
+
+fn span(start: f64, end: f64) -> f64 {
+    if start < 0.0 || end < 0.0 {
+        return 0.0;
+    }
+    (end - start).max(0.0)
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Waterfall {
+    pub fn from_entries(entries: &[NetworkEntry]) -> Self {
+        let origin = entries.iter()
+            .filter(|e| e.started > 0.0)
+            .map(|e| e.started)
+            .fold(f64::INFINITY, f64::min);
+        if !origin.is_finite() {
+            return Self::default();
+        }
+        let mut rows: Vec<WaterfallRow> = entries.iter()
+            .filter(|e| e.started > 0.0)
+            .map(|e| {
+                let start = (e.started - origin) * 1000.0;
+                let end = e.finished
+                    .map(|f| (f - origin) * 1000.0)
+                    .unwrap_or(start);
+                let phases = match &e.timing {
+                    Some(t) => {
+                        let queued = (t.request_time - e.started).max(0.0) * 1000.0;
+                        let first = [t.dns_start, t.connect_start, t.send_start]
+                            .into_iter()
+                            .find(|v| *v >= 0.0)
+                            .unwrap_or(0.0);
+                        let headers = (t.request_time - origin) * 1000.0 + t.receive_headers_end;
+                        WaterfallPhases {
+                            blocked: queued + first,
+                            dns: span(t.dns_start, t.dns_end),
+                            connect: (span(t.connect_start, t.connect_end) - span(t.ssl_start, t.ssl_end)).max(0.0),
+                            ssl: span(t.ssl_start, t.ssl_end),
+                            send: span(t.send_start, t.send_end),
+                            wait: span(t.send_end, t.receive_headers_end),
+                            download: (end - headers).max(0.0)
+                        }
+                    },
+                    None => WaterfallPhases {
+                        download: end - start,
+                        ..Default::default()
+                    }
+                };
+                WaterfallRow {
+                    url: e.url.clone(),
+                    resource_type: e.resource_type.clone(),
+                    status: e.status,
+                    start,
+                    end,
+                    phases,
+                    error: e.error.clone()
+                }
+            })
+            .collect();
+        rows.sort_by(|a, b| a.start.total_cmp(&b.start));
+        let total = rows.iter().map(|r| r.end).fold(0.0, f64::max);
+
+        Self { total, rows }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    pub fn to_svg(&self) -> String {
+        let (label_width, chart_width, row_height) = (320.0, 680.0, 18.0);
+        let height = row_height * (self.rows.len() as f64 + 2.0);
+        let scale = match self.total > 0.0 {
+            true => chart_width / self.total,
+            false => 0.0
+        };
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{height}\" font-family=\"monospace\" font-size=\"11\">\n",
+            label_width + chart_width + 20.0
+        );
+        for (idx, row) in self.rows.iter().enumerate() {
+            let y = row_height * (idx as f64 + 1.0);
+            let label: String = row.url.chars().take(48).collect();
+            svg.push_str(&format!(
+                "<text x=\"4\" y=\"{}\"><title>{}</title>{}</text>\n",
+                y + 12.0, escape_xml(&row.url), escape_xml(&label)
+            ));
+            let mut x = label_width + row.start * scale;
+            for ((name, color), value) in PHASE_COLORS.iter().zip(row.phases.as_array()) {
+                if value <= 0.0 {
+                    continue;
+                }
+                let width = (value * scale).max(1.0);
+                svg.push_str(&format!(
+                    "<rect x=\"{x:.1}\" y=\"{:.1}\" width=\"{width:.1}\" height=\"{:.1}\" fill=\"{color}\"><title>{name}: {value:.1} ms</title></rect>\n",
+                    y + 3.0, row_height - 6.0
+                ));
+                x += width;
+            }
+        }
+        svg.push_str(&format!(
+            "<text x=\"{label_width}\" y=\"{:.1}\">total: {:.0} ms</text>\n</svg>\n",
+            height - 4.0, self.total
+        ));
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::network::EntryTiming;
+
+    fn entry(url: &str, started: f64, finished: Option<f64>, timing: Option<EntryTiming>) -> NetworkEntry {
+        NetworkEntry {
+            request_id: url.into(),
+            url: url.into(),
+            method: "GET".into(),
+            resource_type: Some(ResourceType::Document),
+            status: Some(200),
+            mime_type: None,
+            encoded_size: 0.0,
+            started,
+            finished,
+            error: None,
+            timing,
+            wall_time: None
+        }
+    }
+
+    fn timing() -> EntryTiming {
+        EntryTiming {
+            request_time: 10.005,
+            dns_start: 1.0,
+            dns_end: 11.0,
+            connect_start: 11.0,
+            connect_end: 41.0,
+            ssl_start: 21.0,
+            ssl_end: 41.0,
+            send_start: 41.0,
+            send_end: 42.0,
+            receive_headers_end: 100.0
+        }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn phases_add_up_to_the_request_span() {
+        let waterfall = Waterfall::from_entries(&[
+            entry("https://example.com/late.js", 10.2, Some(10.25), None),
+            entry("https://example.com/", 10.0, Some(10.135), Some(timing()))
+        ]);
+        assert!(close(waterfall.total, 250.0));
+        let row = &waterfall.rows[0];
+        assert_eq!(row.url, "https://example.com/");
+        let p = &row.phases;
+        for (value, expected) in p.as_array().into_iter().zip([6.0, 10.0, 10.0, 20.0, 1.0, 58.0, 30.0]) {
+            assert!(close(value, expected), "{:?}", p);
+        }
+        assert!(close(p.as_array().iter().sum(), row.end - row.start));
+        let late = &waterfall.rows[1];
+        assert!(close(late.start, 200.0) && close(late.phases.download, 50.0));
+    }
+
+    #[test]
+    fn connect_never_goes_negative_on_reused_connections() {
+        let reused = EntryTiming {
+            connect_start: -1.0,
+            connect_end: -1.0,
+            dns_start: -1.0,
+            dns_end: -1.0,
+            ..timing()
+        };
+        let waterfall = Waterfall::from_entries(&[entry("https://example.com/", 10.0, Some(10.135), Some(reused))]);
+        let p = &waterfall.rows[0].phases;
+        assert_eq!((p.dns, p.connect), (0.0, 0.0));
+        assert!(close(p.blocked, 46.0));
+    }
+
+    #[test]
+    fn empty_entries_give_an_empty_waterfall() {
+        let waterfall = Waterfall::from_entries(&[]);
+        assert!(waterfall.rows.is_empty() && waterfall.total == 0.0);
+        let svg = waterfall.to_svg();
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+        assert!(!svg.contains("<rect"));
+    }
+
+    #[test]
+    fn svg_escapes_urls() {
+        let url = "https://example.com/?q=<script>&x=\"1\"";
+        let svg = Waterfall::from_entries(&[entry(url, 10.0, Some(10.1), None)]).to_svg();
+        assert!(!svg.contains("<script>"));
+        assert!(svg.contains("?q=&lt;script&gt;&amp;x=&quot;1&quot;"));
+    }
+}
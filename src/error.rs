@@ -41,6 +41,60 @@ pub enum BrowserError {
     #[error("invalid URL")]
     InvalidUrl,
 
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+
+    #[error("extension control pages disabled")]
+    ControlPagesDisabled,
+
+    #[error("proxy was not applied")]
+    ProxyNotApplied,
+
+    #[error("no proxy available")]
+    ProxyNotFound,
+
+    #[error("proxy failed to relay the request")]
+    ProxyDead,
+
+    #[error("blocked by a captcha challenge")]
+    BlockedCaptcha,
+
+    #[error("target crashed")]
+    TargetCrashed,
+
+    #[error("unknown country code")]
+    UnknownCountry,
+
+    #[error("job budget exceeded")]
+    BudgetExceeded,
+
+    #[error("ip lookup failed: {0}")]
+    IpLookup(String),
+
+    #[error("invalid chrome flags: {0}")]
+    InvalidFlags(String),
+
+    #[error("artifact sink failed: {0}")]
+    Sink(String),
+
+    #[error("frontier backend failed: {0}")]
+    Frontier(String),
+
+    #[error("browser context page quota exceeded")]
+    ContextQuotaExceeded,
+
+    #[error("browser context lifetime expired")]
+    ContextExpired,
+
+    #[error("user callback panicked: {0}")]
+    CallbackPanicked(String),
+
+    #[error("operation cancelled")]
+    Cancelled,
+
+    #[error("human handoff closed without resuming")]
+    HandoffClosed,
+
     #[error("invalid browser config")]
     BuildBrowserConfigError,
 
@@ -48,12 +102,52 @@ pub enum BrowserError {
     Unknown,
 }
 
+impl BrowserError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            BrowserError::PageCreation => "PAGE_CREATE_FAILED",
+            BrowserError::WebSocket => "CDP_WEBSOCKET",
+            BrowserError::Timeout => "TIMEOUT",
+            BrowserError::NetworkIO => "NETWORK_IO",
+            BrowserError::BrowserLaunch => "LAUNCH_FAILED",
+            BrowserError::FrameNotFound => "FRAME_NOT_FOUND",
+            BrowserError::Navigation => "NAV_FAILED",
+            BrowserError::Serialization => "SERIALIZATION",
+            BrowserError::Decoding => "DECODING",
+            BrowserError::ChromeInternal => "CHROME_INTERNAL",
+            BrowserError::JavaScriptError => "JS_EXCEPTION",
+            BrowserError::InvalidUrl => "INVALID_URL",
+            BrowserError::InvalidArgument(_) => "INVALID_ARGUMENT",
+            BrowserError::ControlPagesDisabled => "CONTROL_PAGES_DISABLED",
+            BrowserError::ProxyNotApplied => "PROXY_NOT_APPLIED",
+            BrowserError::ProxyNotFound => "PROXY_NOT_FOUND",
+            BrowserError::ProxyDead => "PROXY_DEAD",
+            BrowserError::BlockedCaptcha => "BLOCKED_CAPTCHA",
+            BrowserError::TargetCrashed => "TARGET_CRASHED",
+            BrowserError::UnknownCountry => "UNKNOWN_COUNTRY",
+            BrowserError::BudgetExceeded => "BUDGET_EXCEEDED",
+            BrowserError::IpLookup(_) => "IP_LOOKUP_FAILED",
+            BrowserError::InvalidFlags(_) => "INVALID_FLAGS",
+            BrowserError::Sink(_) => "SINK_FAILED",
+            BrowserError::Frontier(_) => "FRONTIER_FAILED",
+            BrowserError::ContextQuotaExceeded => "CONTEXT_QUOTA_EXCEEDED",
+            BrowserError::ContextExpired => "CONTEXT_EXPIRED",
+            BrowserError::CallbackPanicked(_) => "CALLBACK_PANICKED",
+            BrowserError::Cancelled => "CANCELLED",
+            BrowserError::HandoffClosed => "HANDOFF_CLOSED",
+            BrowserError::BuildBrowserConfigError => "INVALID_CONFIG",
+            BrowserError::Unknown => "UNKNOWN",
+        }
+    }
+}
+
 impl From<CdpError> for BrowserError {
     fn from(error: CdpError) -> Self {
         match error {
             CdpError::Ws(_) => BrowserError::WebSocket,
             CdpError::Io(_) => BrowserError::NetworkIO,
             CdpError::Serde(_) => BrowserError::Serialization,
+            CdpError::Chrome(e) if e.message.contains("crashed") => BrowserError::TargetCrashed,
             CdpError::Chrome(_) => BrowserError::ChromeInternal,
             CdpError::NoResponse => BrowserError::Timeout,
             CdpError::UnexpectedWsMessage(_) => BrowserError::WebSocket,
@@ -78,3 +172,67 @@ impl From<tokio::time::error::Elapsed> for BrowserError {
         BrowserError::Timeout
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variants() -> Vec<BrowserError> {
+        let text = String::new;
+        vec![
+            BrowserError::PageCreation,
+            BrowserError::WebSocket,
+            BrowserError::Timeout,
+            BrowserError::NetworkIO,
+            BrowserError::BrowserLaunch,
+            BrowserError::FrameNotFound,
+            BrowserError::Navigation,
+            BrowserError::Serialization,
+            BrowserError::Decoding,
+            BrowserError::ChromeInternal,
+            BrowserError::JavaScriptError,
+            BrowserError::InvalidUrl,
+            BrowserError::InvalidArgument(text()),
+            BrowserError::ControlPagesDisabled,
+            BrowserError::ProxyNotApplied,
+            BrowserError::ProxyNotFound,
+            BrowserError::ProxyDead,
+            BrowserError::BlockedCaptcha,
+            BrowserError::TargetCrashed,
+            BrowserError::UnknownCountry,
+            BrowserError::BudgetExceeded,
+            BrowserError::IpLookup(text()),
+            BrowserError::InvalidFlags(text()),
+            BrowserError::Sink(text()),
+            BrowserError::Frontier(text()),
+            BrowserError::ContextQuotaExceeded,
+            BrowserError::ContextExpired,
+            BrowserError::CallbackPanicked(text()),
+            BrowserError::Cancelled,
+            BrowserError::HandoffClosed,
+            BrowserError::BuildBrowserConfigError,
+            BrowserError::Unknown
+        ]
+    }
+
+    #[test]
+    fn codes_match_the_readme_table() {
+        let table: Vec<(String, String)> = include_str!("../README.md")
+            .lines()
+            .skip_while(|line| !line.starts_with("## Error codes"))
+            .filter(|line| line.starts_with("| `"))
+            .map(|line| {
+                let cells: Vec<&str> = line.split('|').map(|c| c.trim().trim_matches('`')).collect();
+                (cells[1].to_owned(), cells[2].to_owned())
+            })
+            .collect();
+        let variants = variants();
+        assert_eq!(table.len(), variants.len());
+        for error in variants {
+            let debug = format!("{error:?}");
+            let name = debug.split('(').next().unwrap();
+            let row = table.iter().find(|(_, variant)| variant == name);
+            assert_eq!(row.map(|(code, _)| code.as_str()), Some(error.code()), "{name}");
+        }
+    }
+}
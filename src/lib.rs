@@ -1,16 +1,166 @@
+#![allow(clippy::redundant_static_lifetimes)]
+
 pub mod error;
 
 mod core;
 pub use core::{
-    DEFAULT_ARGS,
+    ChromeFlags,
+    WebGlMode,
+    GpuStatus,
     BrowserSession,
     BrowserSessionConfig,
+    EffectiveConfig,
+    ControlPages,
+    MediaOptions,
+    ContextParam,
+    BrowserContextHandle,
+    ContextEvent,
+    DisposeReason,
+    split_proxy,
     BrowserError,
     BrowserTimings,
     MyIP,
+    IpProvider,
+    ProxySwitchReport,
+    IpCache,
+    ProxyEntry,
+    ProxyRotator,
+    RotationPolicy,
+    COUNTRY_PROFILES,
+    country_profile,
+    apply_geo,
+    EU_COUNTRIES,
+    is_gdpr_country,
+    GeoVariant,
+    GdprComparison,
+    DeviceKind,
+    Platform,
+    BatteryProfile,
+    ConnectionProfile,
+    FontProfile,
+    VoiceProfile,
+    FingerprintProfile,
+    apply_fingerprint,
+    Identity,
+    IdentityStore,
+    IdentityPolicy,
+    IdentityRotator,
+    cookie_to_param,
+    TimeSpoof,
+    spoof_time,
+    LinkStatus,
+    LinkCheckOptions,
+    collect_links,
+    check_urls,
+    check_links,
+    SeoReport,
+    seo_audit,
+    MutationEvent,
+    watch_mutations,
+    page_expose_function,
+    UploadProgress,
+    set_input_files,
+    watch_uploads,
+    wait_uploads,
+    sniff_encoding,
+    decode_bytes,
+    charset_from_content_type,
+    page_charset,
+    ContentRecord,
+    ContentStore,
+    content_hash,
+    normalize_text,
+    FormFillReport,
+    fill_form,
+    flatten_form,
+    print_pdf,
+    fill_form_to_pdf,
+    FrameText,
+    extract_frame_texts,
+    extract_all_text,
+    DownloadedFile,
+    Navigated,
+    HookFuture,
+    PageHook,
+    PageHooks,
+    Frontier,
+    FrontierFuture,
+    RateLimiter,
+    MemoryFrontier,
+    MemoryRateLimiter,
+    url_host,
+    wait_turn,
+    TabGc,
+    TabGcOptions,
+    TabGcHandle,
+    BrowserPool,
+    BrowserPoolConfig,
+    PooledPage,
+    SiteConfig,
+    BridgePage,
+    PageGuard,
+    CancellationToken,
+    cancellable,
+    Screencast,
+    ScreencastOptions,
+    ServeOptions,
+    OperatorCommand,
+    apply_operator_command,
     PageParam,
+    PageParamOwned,
+    CacheMode,
+    hard_reload,
     random_user_agent,
+    NetworkRecorder,
+    NetworkEntry,
+    EntryTiming,
+    Waterfall,
+    WaterfallRow,
+    WaterfallPhases,
+    Asset,
+    Assets,
+    collect_page_assets,
+    collect_page_assets_reloaded,
+    ConsoleEntry,
+    ConsoleRecorder,
+    ArtifactIndex,
+    JobArtifacts,
+    entries_to_har,
+    ArtifactSink,
+    SinkFuture,
+    DirSink,
+    content_type,
+    to_jsonl,
+    idempotency_key,
+    DeliveryLedger,
+    DedupSink,
+    JobBudget,
+    BudgetGuard,
+    InterceptedRequest,
+    InterceptConfig,
+    Interceptor,
+    RequestHook,
+    ProxyAuth,
+    NetworkResponse,
+    ResponseCapture,
+    TRACKER_BLOCKLIST,
+    MainResponse,
+    RetryPolicy,
+    parse_retry_after,
+    parse_http_date,
+    SecurityInfo,
+    OriginUsage,
+    PruneOptions,
+    profile_size,
+    prune_profile,
+    WarmupPlan,
 };
+#[cfg(feature = "s3")]
+pub use core::{S3Sink, S3SinkConfig};
+#[cfg(feature = "redis")]
+pub use core::{RedisFrontier, RedisRateLimiter};
+#[cfg(feature = "lang")]
+pub use core::{Lang, DetectedLanguage, detect_language, detect_language_info};
 pub use core::extension;
 pub use chromiumoxide;
 